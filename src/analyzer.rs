@@ -1,21 +1,29 @@
-use crate::config::Config;
-use crate::rules::{Rule, RuleContext, Issue};
-use crate::walker::RustFileWalker;
+use crate::config::{Config, PathMatcher};
+use crate::rules::{rules_allowed_in_tests, Applicability, Fix, Rule, RuleContext, Issue, Replacement};
+use crate::walker::{crate_module, RustFileWalker};
+use crate::git::GitSelection;
 use crate::incremental::{IncrementalAnalyzer, IncrementalResults};
 use crate::ast_cache::{ASTCache, read_rust_file};
-use crate::autofix::{AutoFixEngine, ImportOrganizer, NamingConventionFixer, DocTemplateGenerator};
-use ahash::AHashMap;
+use crate::autofix::{AutoFixEngine, ImportOrganizer, NamingConventionFixer, DocTemplateGenerator, SourceFile};
+use ahash::{AHashMap, AHashSet};
 use dashmap::DashMap;
 use rayon::prelude::*;
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Decides whether a fix for an issue in the given file is applied.
+pub type ReviewFn<'a> = &'a mut dyn FnMut(&Path, &Issue) -> bool;
+
 pub struct Analyzer {
     config: Arc<Config>,
     rules: Vec<Box<dyn Rule>>,
     incremental_analyzer: Option<IncrementalAnalyzer>,
     ast_cache: Option<ASTCache>,
     autofix_engine: AutoFixEngine,
+    git_selection: Option<GitSelection>,
+    walker: RustFileWalker,
+    path_matcher: PathMatcher,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -24,6 +32,9 @@ pub struct AnalysisResults {
     pub stats: AnalysisStats,
     pub performance_stats: Option<PerformanceStats>,
     pub fixed_files: Option<AHashMap<PathBuf, String>>,
+    /// Staged files left unfixed because their working copy has unstaged changes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partially_staged: Vec<PathBuf>,
 }
 
 #[derive(Debug, Default, serde::Serialize)]
@@ -64,34 +75,115 @@ impl Analyzer {
             None
         };
         
+        let autofix_engine = AutoFixEngine::with_config(&config.autofix);
+        let walker = RustFileWalker::with_config(&config);
+        let path_matcher = config.path_matcher();
+        
         Self {
             config: Arc::new(config),
             rules,
             incremental_analyzer,
             ast_cache,
-            autofix_engine: AutoFixEngine::new(),
+            autofix_engine,
+            git_selection: None,
+            walker,
+            path_matcher,
         }
     }
     
+    /// Stops respecting `.gitignore`, `.ignore` and `.flignore` files.
+    pub fn without_ignore_files(mut self) -> Self {
+        self.walker = self.walker.ignore_files(false);
+        self
+    }
+    
+    /// Whether `path` is left out by the config globs or ignore files.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.walker.is_ignored(path)
+    }
+    
+    /// Restricts analysis to the files, and with `lines_only` the lines,
+    /// that `selection` covers.
+    pub fn with_git_selection(mut self, selection: GitSelection) -> Self {
+        self.git_selection = Some(selection);
+        self
+    }
+    
     pub fn analyze_path(&mut self, path: &Path) -> AnalysisResults {
-        self.analyze_path_with_options(path, false)
+        self.analyze_paths(&[path.to_path_buf()])
     }
     
     pub fn analyze_path_with_autofix(&mut self, path: &Path) -> AnalysisResults {
-        self.analyze_path_with_options(path, true)
+        self.analyze_paths_with_autofix(&[path.to_path_buf()])
+    }
+    
+    /// Like `analyze_path_with_autofix`, but only applies the fixes that
+    /// `review` accepts. Fixes are offered file by file, in line order.
+    pub fn analyze_path_with_review(
+        &mut self,
+        path: &Path,
+        review: ReviewFn<'_>,
+    ) -> AnalysisResults {
+        self.analyze_paths_with_review(&[path.to_path_buf()], review)
     }
     
-    fn analyze_path_with_options(&mut self, path: &Path, apply_autofix: bool) -> AnalysisResults {
+    /// Analyzes every file under each of `paths`, which may be files or
+    /// directories. Files reached through more than one path count once.
+    pub fn analyze_paths(&mut self, paths: &[PathBuf]) -> AnalysisResults {
+        self.analyze_paths_with_options(paths, None)
+    }
+    
+    pub fn analyze_paths_with_autofix(&mut self, paths: &[PathBuf]) -> AnalysisResults {
+        self.analyze_paths_with_options(paths, Some(&mut |_, _| true))
+    }
+    
+    pub fn analyze_paths_with_review(
+        &mut self,
+        paths: &[PathBuf],
+        review: ReviewFn<'_>,
+    ) -> AnalysisResults {
+        self.analyze_paths_with_options(paths, Some(review))
+    }
+    
+    fn analyze_paths_with_options(
+        &mut self,
+        paths: &[PathBuf],
+        review: Option<ReviewFn<'_>>,
+    ) -> AnalysisResults {
         let start_time = std::time::Instant::now();
         
-        let walker = RustFileWalker::new();
-        let files: Vec<_> = walker.walk(path).collect();
+        let mut seen = AHashSet::new();
+        let files: Vec<_> = paths
+            .iter()
+            .flat_map(|path| self.walker.walk(path))
+            .filter(|file| seen.insert(file.canonicalize().unwrap_or_else(|_| file.clone())))
+            .filter(|file| self.git_selection.as_ref().is_none_or(|selection| selection.contains_file(file)))
+            .collect();
         
         // Use incremental analysis if available
         let total_files = files.len();
         
-        let (file_issues, mut performance_stats) = if let Some(ref mut incremental) = self.incremental_analyzer {
-            let incremental_results = incremental.analyze_files(files);
+        let (file_issues, mut performance_stats) = if let Some(selection) = self.git_selection.as_ref().filter(|selection| selection.is_staged()) {
+            // Lint what is about to be committed rather than the working tree
+            let file_issues = files
+                .iter()
+                .filter_map(|file_path| {
+                    let issues = self.analyze_source(file_path, selection.staged_source(file_path)?)?;
+                    Some((file_path.clone(), issues))
+                })
+                .collect();
+            let perf_stats = PerformanceStats {
+                cache_hit_rate: 0.0,
+                files_from_cache: 0,
+                analysis_time_ms: start_time.elapsed().as_millis(),
+                memory_usage_mb: None,
+                autofix_time_ms: None,
+                fixes_applied: 0,
+            };
+            
+            (file_issues, Some(perf_stats))
+        } else if let Some(ref mut incremental) = self.incremental_analyzer {
+            let incremental_results = incremental.analyze_files(files.clone());
             let all_issues = incremental_results.all_issues();
             
             let perf_stats = PerformanceStats {
@@ -119,33 +211,59 @@ impl Analyzer {
             (file_issues, Some(perf_stats))
         };
         
+        // Drop findings outside the selected lines
+        let mut file_issues = file_issues;
+        if let Some(selection) = &self.git_selection {
+            for (file_path, issues) in file_issues.iter_mut() {
+                issues.retain(|issue| selection.contains_line(file_path, issue.location.line));
+            }
+            file_issues.retain(|_, issues| !issues.is_empty());
+        }
+        
         // Apply auto-fixes if requested and enabled
         let mut fixed_files = None;
-        let mut total_fixes_applied = 0;
+        let mut partially_staged = Vec::new();
         
-        if apply_autofix && self.config.autofix.enabled {
+        if let Some(review) = review.filter(|_| self.config.autofix.enabled) {
             let autofix_start = std::time::Instant::now();
+            
+            if self.config.autofix.fix_naming_conventions {
+                self.expand_rename_fixes(&files, &mut file_issues);
+            } else {
+                for issue in file_issues.values_mut().flatten() {
+                    if issue.rule == "naming-convention" {
+                        issue.fix = None;
+                    }
+                }
+            }
+            
+            let accepted = self.review_fixes(&file_issues, review);
             let mut fixes = AHashMap::new();
             
-            for (file_path, issues) in &file_issues {
-                if let Ok(content) = read_rust_file(file_path) {
-                    if let Ok(fixed_content) = self.autofix_engine.apply_fixes(&content, issues) {
+            for (file_path, issues) in group_fixes_by_file(&accepted) {
+                if let Ok(content) = read_rust_file(&file_path) {
+                    // Findings from the index only line up with an identical working copy
+                    let staged = self.git_selection.as_ref().and_then(|selection| selection.staged_source(&file_path));
+                    if staged.is_some_and(|staged| staged != content) {
+                        partially_staged.push(file_path);
+                        continue;
+                    }
+                    if let Ok(fixed_content) = self.autofix_engine.apply_fixes(&content, &issues) {
                         if fixed_content != content {
-                            fixes.insert(file_path.clone(), fixed_content);
+                            fixes.insert(file_path, fixed_content);
                         }
                     }
                 }
             }
             
-            total_fixes_applied = self.autofix_engine.fixes_applied;
             if !fixes.is_empty() {
                 fixed_files = Some(fixes);
             }
             
             // Update performance stats with autofix timing
-            if let Some(ref mut perf_stats) = performance_stats.as_mut() {
+            if let Some(perf_stats) = performance_stats.as_mut() {
                 perf_stats.autofix_time_ms = Some(autofix_start.elapsed().as_millis());
-                perf_stats.fixes_applied = total_fixes_applied;
+                perf_stats.fixes_applied = self.autofix_engine.fixes_applied;
             }
         }
         
@@ -168,7 +286,152 @@ impl Analyzer {
             stats,
             performance_stats,
             fixed_files,
+            partially_staged,
+        }
+    }
+    
+    /// Keeps only the fixes that `review` accepts, skipping ones the engine
+    /// would not apply anyway.
+    fn review_fixes(
+        &self,
+        file_issues: &AHashMap<PathBuf, Vec<Issue>>,
+        review: ReviewFn<'_>,
+    ) -> AHashMap<PathBuf, Vec<Issue>> {
+        let mut paths: Vec<_> = file_issues.keys().collect();
+        paths.sort();
+        
+        let mut accepted = AHashMap::new();
+        
+        for file_path in paths {
+            let mut issues: Vec<_> = file_issues[file_path]
+                .iter()
+                .filter(|issue| {
+                    issue.fix.as_ref().is_some_and(|fix| {
+                        !fix.replacements.is_empty()
+                            && (fix.applicability == Applicability::Safe || !self.autofix_engine.safe_fixes_only)
+                    })
+                })
+                .collect();
+            issues.sort_by_key(|issue| (issue.location.line, issue.location.column));
+            
+            let kept: Vec<Issue> = issues
+                .into_iter()
+                .filter(|issue| review(file_path, issue))
+                .cloned()
+                .collect();
+            
+            if !kept.is_empty() {
+                accepted.insert(file_path.clone(), kept);
+            }
         }
+        
+        accepted
+    }
+    
+    /// Extends single-file rename fixes to every reference in the analyzed tree.
+    fn expand_rename_fixes(&self, files: &[PathBuf], file_issues: &mut AHashMap<PathBuf, Vec<Issue>>) {
+        let fixer = NamingConventionFixer::new();
+        let mut renames = Vec::new();
+        let mut issue_refs = Vec::new();
+        
+        for (file_path, issues) in file_issues.iter() {
+            let Ok(content) = read_rust_file(file_path) else { continue };
+            for (index, issue) in issues.iter().enumerate() {
+                if issue.rule != "naming-convention" {
+                    continue;
+                }
+                if let Some(rename) = issue.fix.as_ref().and_then(|fix| fixer.rename_of(fix, &content)) {
+                    renames.push(rename);
+                    issue_refs.push((file_path.clone(), index));
+                }
+            }
+        }
+        
+        if renames.is_empty() {
+            return;
+        }
+        
+        // Items can be named anywhere in their crate, so search every file
+        // of the crates they are declared in, not just the selected ones
+        let canonical = |path: &PathBuf| path.canonicalize().unwrap_or_else(|_| path.clone());
+        let roots: BTreeSet<PathBuf> = renames.iter().map(|rename| crate_module(&rename.file).0).collect();
+        let crate_files: Vec<PathBuf> = roots
+            .iter()
+            .flat_map(|root| RustFileWalker::new().ignore_files(false).walk(root))
+            .collect();
+        let mut seen = AHashSet::new();
+        let paths: Vec<&PathBuf> = files
+            .iter()
+            .chain(&crate_files)
+            .filter(|path| seen.insert(canonical(path)))
+            .collect();
+        
+        // Syntax trees are not `Send`, so parse on this thread
+        let mut unparsed = AHashSet::new();
+        let parsed: Vec<(PathBuf, syn::File)> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let syntax_tree = read_rust_file(path).ok().and_then(|content| syn::parse_file(&content).ok());
+                if syntax_tree.is_none() {
+                    unparsed.insert(canonical(path));
+                }
+                Some((path.clone(), syntax_tree?))
+            })
+            .collect();
+        let sources: Vec<SourceFile> = parsed
+            .iter()
+            .map(|(path, syntax_tree)| SourceFile { path, syntax_tree })
+            .collect();
+        let whole_crate = crate_files.iter().all(|path| !unparsed.contains(&canonical(path)));
+        
+        let fixes = fixer.create_rename_fixes(&renames, &sources, whole_crate);
+        for ((file_path, index), fix) in issue_refs.into_iter().zip(fixes) {
+            if let (Some(issues), Some(fix)) = (file_issues.get_mut(&file_path), fix) {
+                issues[index].fix = Some(fix);
+            }
+        }
+    }
+    
+    /// Lints `content` as if it were the contents of `path`, without touching
+    /// the disk. Returns `None` when the source doesn't parse.
+    pub fn analyze_source(&self, path: &Path, content: &str) -> Option<Vec<Issue>> {
+        let syntax_tree = syn::parse_file(content).ok()?;
+        let mut ctx = RuleContext::new(
+            path.to_path_buf(),
+            content.to_string(),
+            syntax_tree,
+        )
+        .allow_in_tests(&rules_allowed_in_tests(&self.config));
+        
+        // Apply each rule not switched off for this path
+        for rule in &self.rules {
+            if !self.path_matcher.is_rule_disabled(path, rule.name()) {
+                rule.check(&mut ctx);
+            }
+        }
+        
+        Some(ctx.into_issues())
+    }
+    
+    /// Applies the fixes `analyze_path_with_autofix` would apply to
+    /// `issues` found in `content`, returning the fixed source.
+    pub fn fix_source(&mut self, path: &Path, content: &str, issues: &[Issue]) -> String {
+        if !self.config.autofix.enabled {
+            return content.to_string();
+        }
+        
+        let mut issues = issues.to_vec();
+        if !self.config.autofix.fix_naming_conventions {
+            issues.retain(|issue| issue.rule != "naming-convention");
+        }
+        
+        let file_issues = AHashMap::from_iter([(path.to_path_buf(), issues)]);
+        let accepted = self.review_fixes(&file_issues, &mut |_, _| true);
+        let fixes = group_fixes_by_file(&accepted).remove(path).unwrap_or_default();
+        
+        self.autofix_engine
+            .apply_fixes(content, &fixes)
+            .unwrap_or_else(|_| content.to_string())
     }
     
     fn analyze_files_parallel(&self, files: &[PathBuf]) -> AHashMap<PathBuf, Vec<Issue>> {
@@ -214,34 +477,26 @@ impl Analyzer {
             file_path.to_path_buf(),
             content,
             syntax_tree,
-        );
+        )
+        .allow_in_tests(&rules_allowed_in_tests(&self.config));
         
-        // Apply each rule
+        // Apply each rule not switched off for this path
         for rule in &self.rules {
-            rule.check(&mut ctx);
+            if !self.path_matcher.is_rule_disabled(file_path, rule.name()) {
+                rule.check(&mut ctx);
+            }
         }
         
-        Some(ctx.issues)
+        Some(ctx.into_issues())
     }
-
+    
     pub fn analyze_file(&self, path: &Path) -> AnalysisResults {
         let mut file_issues = AHashMap::new();
         
         if let Ok(content) = std::fs::read_to_string(path) {
-            if let Ok(syntax_tree) = syn::parse_file(&content) {
-                let mut ctx = RuleContext::new(
-                    path.to_path_buf(),
-                    content.clone(),
-                    syntax_tree,
-                );
-                
-                // Apply each rule
-                for rule in &self.rules {
-                    rule.check(&mut ctx);
-                }
-                
-                if !ctx.issues.is_empty() {
-                    file_issues.insert(path.to_path_buf(), ctx.issues);
+            if let Some(issues) = self.analyze_source(path, &content) {
+                if !issues.is_empty() {
+                    file_issues.insert(path.to_path_buf(), issues);
                 }
             }
         }
@@ -264,8 +519,39 @@ impl Analyzer {
             stats,
             performance_stats: None,
             fixed_files: None,
+            partially_staged: Vec::new(),
+        }
+    }
+}
+
+/// Splits fixes by the file each replacement targets, so that fixes spanning
+/// several files (such as renames) are applied to all of them.
+fn group_fixes_by_file(file_issues: &AHashMap<PathBuf, Vec<Issue>>) -> AHashMap<PathBuf, Vec<Issue>> {
+    let mut grouped: AHashMap<PathBuf, Vec<Issue>> = AHashMap::new();
+    
+    for (file_path, issues) in file_issues {
+        for issue in issues {
+            let Some(fix) = &issue.fix else { continue };
+            
+            let mut by_file: AHashMap<PathBuf, Vec<Replacement>> = AHashMap::new();
+            for replacement in &fix.replacements {
+                let target = replacement.file.clone().unwrap_or_else(|| file_path.clone());
+                by_file.entry(target).or_default().push(replacement.clone());
+            }
+            
+            for (target, replacements) in by_file {
+                grouped.entry(target).or_default().push(Issue {
+                    fix: Some(Fix {
+                        replacements,
+                        ..fix.clone()
+                    }),
+                    ..issue.clone()
+                });
+            }
         }
     }
+    
+    grouped
 }
 
 impl AnalysisResults {
@@ -313,4 +599,40 @@ impl AnalysisResults {
     pub fn has_fixes(&self) -> bool {
         self.fixed_files.is_some()
     }
+    
+    /// Adds the results of analyzing other files, such as paths checked with
+    /// a config of their own.
+    pub fn merge(&mut self, other: AnalysisResults) {
+        self.file_issues.extend(other.file_issues);
+        self.stats.total_files += other.stats.total_files;
+        self.stats.files_with_issues += other.stats.files_with_issues;
+        self.stats.total_issues += other.stats.total_issues;
+        for (severity, count) in other.stats.issues_by_severity {
+            *self.stats.issues_by_severity.entry(severity).or_insert(0) += count;
+        }
+        
+        self.performance_stats = match (self.performance_stats.take(), other.performance_stats) {
+            (Some(mut stats), Some(other_stats)) => {
+                stats.files_from_cache += other_stats.files_from_cache;
+                stats.cache_hit_rate = stats.files_from_cache as f64 / self.stats.total_files.max(1) as f64;
+                stats.analysis_time_ms += other_stats.analysis_time_ms;
+                stats.memory_usage_mb = match (stats.memory_usage_mb, other_stats.memory_usage_mb) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+                stats.autofix_time_ms = match (stats.autofix_time_ms, other_stats.autofix_time_ms) {
+                    (Some(a), Some(b)) => Some(a + b),
+                    (a, b) => a.or(b),
+                };
+                stats.fixes_applied += other_stats.fixes_applied;
+                Some(stats)
+            }
+            (stats, other_stats) => stats.or(other_stats),
+        };
+        
+        if let Some(fixed_files) = other.fixed_files {
+            self.fixed_files.get_or_insert_with(AHashMap::new).extend(fixed_files);
+        }
+        self.partially_staged.extend(other.partially_staged);
+    }
 }
\ No newline at end of file
@@ -28,6 +28,8 @@ pub struct MmapFileReader {
 impl MmapFileReader {
     pub fn new(path: &Path) -> io::Result<Self> {
         let file = File::open(path)?;
+        // SAFETY: the map is only read, and lives no longer than the reader;
+        // as with any mapping, the file must not be truncated meanwhile
         let mmap = unsafe { Mmap::map(&file)? };
         
         Ok(Self {
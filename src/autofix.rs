@@ -1,67 +1,80 @@
-use crate::rules::{Fix, Issue, Location, Replacement};
-use std::path::Path;
+use crate::config::AutoFixConfig;
+use crate::rules::{self_type_name, Applicability, Fix, Issue, Replacement};
+use crate::walker::crate_module;
+use ahash::{AHashMap, AHashSet};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use syn::ext::IdentExt;
+use syn::visit::Visit;
 use syn::{File as SynFile, Item, ItemUse, UseTree};
 
 pub struct AutoFixEngine {
     pub fixes_applied: usize,
+    pub safe_fixes_only: bool,
 }
 
 impl AutoFixEngine {
     pub fn new() -> Self {
         Self {
             fixes_applied: 0,
+            safe_fixes_only: false,
+        }
+    }
+    
+    pub fn with_config(config: &AutoFixConfig) -> Self {
+        Self {
+            fixes_applied: 0,
+            safe_fixes_only: config.apply_safe_fixes_only,
         }
     }
     
     pub fn apply_fixes(&mut self, content: &str, issues: &[Issue]) -> Result<String, Box<dyn std::error::Error>> {
-        let mut fixed_content = content.to_string();
-        let mut offset_adjustment = 0i64;
-        
-        // Sort fixes by position (reverse order to maintain positions)
         let mut fixes_with_positions: Vec<_> = issues
             .iter()
             .filter_map(|issue| issue.fix.as_ref().map(|fix| (issue, fix)))
+            .filter(|(_, fix)| !fix.replacements.is_empty())
+            .filter(|(_, fix)| !self.safe_fixes_only || fix.applicability == Applicability::Safe)
             .collect();
         
-        // Sort by start position in reverse order
-        fixes_with_positions.sort_by_key(|(issue, _)| std::cmp::Reverse(issue.location.line));
+        fixes_with_positions.sort_by_key(|(_, fix)| fix.replacements.iter().map(|r| r.start).min().unwrap_or(0));
         
-        for (issue, fix) in fixes_with_positions {
-            match self.apply_single_fix(&mut fixed_content, issue, fix, &mut offset_adjustment) {
-                Ok(true) => self.fixes_applied += 1,
-                Ok(false) => {}, // Fix not applicable
-                Err(e) => eprintln!("Warning: Failed to apply fix for {}: {}", issue.rule, e),
-            }
-        }
+        // Accept fixes whose edits don't collide with an already accepted fix
+        let mut accepted: Vec<&Replacement> = Vec::new();
         
-        Ok(fixed_content)
-    }
-    
-    fn apply_single_fix(
-        &self,
-        content: &mut String,
-        issue: &Issue,
-        fix: &Fix,
-        offset_adjustment: &mut i64,
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        for replacement in &fix.replacements {
-            let start = (replacement.start as i64 + *offset_adjustment) as usize;
-            let end = (replacement.end as i64 + *offset_adjustment) as usize;
-            
-            if start > content.len() || end > content.len() || start > end {
-                return Ok(false); // Invalid range, skip this fix
+        for (_, fix) in fixes_with_positions {
+            if !self.is_applicable(content, fix) {
+                continue; // Invalid range, skip this fix
             }
             
-            let original_len = end - start;
-            let new_len = replacement.text.len();
-            
-            content.replace_range(start..end, &replacement.text);
+            let overlaps = fix.replacements.iter().any(|new| {
+                accepted.iter().any(|old| new.start < old.end && old.start < new.end)
+            });
+            if overlaps {
+                continue;
+            }
             
-            // Update offset for subsequent fixes
-            *offset_adjustment += new_len as i64 - original_len as i64;
+            accepted.extend(fix.replacements.iter());
+            self.fixes_applied += 1;
+        }
+        
+        // Apply from the end of the file backwards so earlier offsets stay valid
+        accepted.sort_by_key(|r| std::cmp::Reverse((r.start, r.end)));
+        
+        let mut fixed_content = content.to_string();
+        for replacement in accepted {
+            fixed_content.replace_range(replacement.start..replacement.end, &replacement.text);
         }
         
-        Ok(true)
+        Ok(fixed_content)
+    }
+    
+    fn is_applicable(&self, content: &str, fix: &Fix) -> bool {
+        fix.replacements.iter().all(|replacement| {
+            replacement.start <= replacement.end
+                && replacement.end <= content.len()
+                && content.is_char_boundary(replacement.start)
+                && content.is_char_boundary(replacement.end)
+        })
     }
 }
 
@@ -252,7 +265,9 @@ impl ImportOrganizer {
                 start: 0,
                 end: content.len(),
                 text: organized,
+                file: None,
             }],
+            applicability: Applicability::Safe,
         };
         
         Ok(Some(fix))
@@ -262,79 +277,1028 @@ impl ImportOrganizer {
 // Naming convention fixes
 pub struct NamingConventionFixer;
 
+/// A parsed file that rename fixes search for references.
+pub struct SourceFile<'a> {
+    pub path: &'a Path,
+    pub syntax_tree: &'a SynFile,
+}
+
+/// An item to rename across the workspace, identified by where its name
+/// is declared.
+#[derive(Debug, Clone)]
+pub struct Rename {
+    pub file: PathBuf,
+    /// Byte offset of the name in the declaration
+    pub offset: usize,
+    pub from: String,
+    pub to: String,
+}
+
 impl NamingConventionFixer {
     pub fn new() -> Self {
         Self
     }
     
-    pub fn create_snake_case_fix(&self, identifier: &str, location: &Location) -> Option<Fix> {
-        let snake_case = self.to_snake_case(identifier);
-        
-        if snake_case == identifier {
+    pub fn create_snake_case_fix(&self, file: &Path, ident: &syn::Ident, files: &[SourceFile]) -> Option<Fix> {
+        let snake_case = self.to_snake_case(&ident.unraw().to_string());
+        self.create_rename_fix(file, ident, &snake_case, files)
+    }
+    
+    pub fn create_pascal_case_fix(&self, file: &Path, ident: &syn::Ident, files: &[SourceFile]) -> Option<Fix> {
+        let pascal_case = self.to_pascal_case(&ident.unraw().to_string());
+        self.create_rename_fix(file, ident, &pascal_case, files)
+    }
+    
+    pub fn create_upper_case_fix(&self, file: &Path, ident: &syn::Ident, files: &[SourceFile]) -> Option<Fix> {
+        let upper_case = self.to_upper_case(&ident.unraw().to_string());
+        self.create_rename_fix(file, ident, &upper_case, files)
+    }
+    
+    /// Renames the item declared as `ident` in `file`. Only `files` are
+    /// searched, so the fix is never marked safe.
+    pub fn create_rename_fix(&self, file: &Path, ident: &syn::Ident, to: &str, files: &[SourceFile]) -> Option<Fix> {
+        let from = ident.unraw().to_string();
+        if from == to {
             return None;
         }
         
-        Some(Fix {
-            description: format!("Convert '{}' to snake_case: '{}'", identifier, snake_case),
-            replacements: vec![Replacement {
-                start: location.column.saturating_sub(1),
-                end: location.column + identifier.len() - 1,
-                text: snake_case,
-            }],
+        let rename = Rename {
+            file: file.to_path_buf(),
+            offset: ident.span().byte_range().start,
+            from,
+            to: to.to_string(),
+        };
+        self.create_rename_fixes(std::slice::from_ref(&rename), files, false).pop().flatten()
+    }
+    
+    /// Builds one fix per rename, covering the declaration and every
+    /// reference in `files` that resolves to it: paths through the module
+    /// tree and `use` items, `Type::method` and `Self::method` paths, method
+    /// calls when no other method has the name, and macro arguments.
+    ///
+    /// Renames that could change what some name refers to get no fix: the
+    /// new name is already in scope where the item is named, a local binding
+    /// shadows the old one, or a method call can't be told apart from
+    /// another type's. Fixes are only safe for private items, and only when
+    /// `whole_crate` says `files` include every file of their crate and no
+    /// reference is inside a macro.
+    pub fn create_rename_fixes(&self, renames: &[Rename], files: &[SourceFile], whole_crate: bool) -> Vec<Option<Fix>> {
+        let mut symbols = SymbolTable::default();
+        for file in files {
+            let mut collector = DeclarationCollector {
+                symbols: &mut symbols,
+                path: file.path,
+                scope: Scope::of_file(file.path),
+                owner: None,
+            };
+            collector.visit_file(file.syntax_tree);
+        }
+        
+        let mut targets: Vec<Option<Target>> = renames.iter().map(|rename| symbols.target(rename)).collect();
+        let names: AHashSet<String> = targets.iter().flatten().map(|target| target.name.clone()).collect();
+        for file in files {
+            let mut collector = ReferenceCollector {
+                symbols: &symbols,
+                names: &names,
+                targets: &mut targets,
+                path: file.path,
+                scope: Scope::of_file(file.path),
+                owners: Vec::new(),
+            };
+            collector.visit_file(file.syntax_tree);
+        }
+        
+        // Two renames to the same name in one scope would clash with each other
+        let mut destinations: AHashMap<(Scope, Namespace, &str), usize> = AHashMap::new();
+        for (rename, target) in renames.iter().zip(&targets) {
+            if let Some(target) = target {
+                let declaration = &symbols.declarations[target.declaration];
+                *destinations
+                    .entry((declaration.scope.clone(), declaration.namespace.clone(), rename.to.as_str()))
+                    .or_default() += 1;
+            }
+        }
+        
+        renames
+            .iter()
+            .zip(targets)
+            .map(|(rename, target)| {
+                let target = target?;
+                let declaration = &symbols.declarations[target.declaration];
+                let key = (declaration.scope.clone(), declaration.namespace.clone(), rename.to.as_str());
+                if target.conflict || destinations[&key] > 1 || symbols.clashes(&target, &rename.to) {
+                    return None;
+                }
+                
+                let mut found = target.sites;
+                let declared_at = (declaration.file.clone(), declaration.start, declaration.end);
+                found.remove(&declared_at);
+                let file_count = found
+                    .iter()
+                    .map(|(path, _, _)| path)
+                    .chain([&declaration.file])
+                    .collect::<BTreeSet<_>>()
+                    .len();
+                let description = format!(
+                    "Rename '{}' to '{}' ({} occurrences in {} files)",
+                    rename.from,
+                    rename.to,
+                    found.len() + 1,
+                    file_count
+                );
+                
+                // The declaration comes first, for `rename_of`
+                Some(Fix {
+                    description,
+                    replacements: [declared_at]
+                        .into_iter()
+                        .chain(found)
+                        .map(|(path, start, end)| Replacement {
+                            start,
+                            end,
+                            text: rename.to.clone(),
+                            file: Some(path),
+                        })
+                        .collect(),
+                    applicability: if declaration.is_pub || !whole_crate || target.in_macro {
+                        Applicability::Unsafe
+                    } else {
+                        Applicability::Safe
+                    },
+                })
+            })
+            .collect()
+    }
+    
+    /// Recovers the rename carried by a fix built with `create_rename_fix`.
+    pub fn rename_of(&self, fix: &Fix, content: &str) -> Option<Rename> {
+        let first = fix.replacements.first()?;
+        let from = content.get(first.start..first.end)?;
+        
+        Some(Rename {
+            file: first.file.clone()?,
+            offset: first.start,
+            from: from.trim_start_matches("r#").to_string(),
+            to: first.text.clone(),
         })
     }
     
-    pub fn create_pascal_case_fix(&self, identifier: &str, location: &Location) -> Option<Fix> {
-        let pascal_case = self.to_pascal_case(identifier);
+    /// `fooBar` and `FooBar` to `foo_bar`, keeping acronyms together
+    /// (`XMLHttpRequest` to `xml_http_request`) and leading underscores.
+    pub fn to_snake_case(&self, s: &str) -> String {
+        let body = s.trim_start_matches('_');
+        let leading = &s[..s.len() - body.len()];
+        format!("{}{}", leading, split_words(body).join("_"))
+    }
+    
+    /// `foo_bar` and `HTTPServer` to `FooBar` and `HttpServer`. Words whose
+    /// boundary has no case, like `v1_2`, stay separated.
+    pub fn to_pascal_case(&self, s: &str) -> String {
+        let body = s.trim_start_matches('_');
+        let mut result = s[..s.len() - body.len()].to_string();
         
-        if pascal_case == identifier {
-            return None;
+        for word in split_words(body) {
+            let joins_without_case = result.chars().last().is_some_and(|last| !has_case(last))
+                && word.chars().next().is_some_and(|first| !has_case(first));
+            if joins_without_case && !result.ends_with('_') {
+                result.push('_');
+            }
+            
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.push_str(chars.as_str());
+            }
         }
         
-        Some(Fix {
-            description: format!("Convert '{}' to PascalCase: '{}'", identifier, pascal_case),
-            replacements: vec![Replacement {
-                start: location.column.saturating_sub(1),
-                end: location.column + identifier.len() - 1,
-                text: pascal_case,
-            }],
+        result
+    }
+    
+    /// `fooBar` to `FOO_BAR`.
+    pub fn to_upper_case(&self, s: &str) -> String {
+        self.to_snake_case(s).to_uppercase()
+    }
+}
+
+/// Lowercase words of an identifier, split at underscores and case changes.
+/// A run of capitals is one word, except for a last capital that starts a
+/// lowercase word.
+fn split_words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    
+    for part in s.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        
+        for (i, &ch) in chars.iter().enumerate() {
+            let prev = i.checked_sub(1).map(|prev| chars[prev]);
+            let next = chars.get(i + 1);
+            let starts_word = ch.is_uppercase()
+                && prev.is_some_and(|prev| {
+                    !prev.is_uppercase() || next.is_some_and(|next| next.is_lowercase())
+                });
+            
+            if starts_word && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.extend(ch.to_lowercase());
+        }
+        
+        words.push(word);
+    }
+    
+    words
+}
+
+fn has_case(ch: char) -> bool {
+    ch.is_uppercase() || ch.is_lowercase()
+}
+
+/// Names in the standard prelude. An item renamed to one of these would
+/// shadow it in every module that glob-imports the item's module.
+const PRELUDE: &[&str] = &[
+    "Option", "Some", "None", "Result", "Ok", "Err", "String", "Vec", "Box", "ToString", "ToOwned", "Clone",
+    "Copy", "Send", "Sync", "Sized", "Unpin", "Default", "Drop", "Fn", "FnMut", "FnOnce", "Iterator",
+    "IntoIterator", "DoubleEndedIterator", "ExactSizeIterator", "Extend", "From", "Into", "TryFrom",
+    "TryInto", "FromIterator", "AsRef", "AsMut", "Eq", "PartialEq", "Ord", "PartialOrd", "drop",
+];
+
+/// How deep `use` chains and glob imports are followed.
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// A module, or a block with items of its own, as the source root of its
+/// crate and the path to it from there. Blocks are path segments starting
+/// with `{`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Scope {
+    root: PathBuf,
+    path: Vec<String>,
+}
+
+impl Scope {
+    fn of_file(path: &Path) -> Self {
+        let (root, path) = crate_module(path);
+        Self { root, path }
+    }
+    
+    fn child(&self, name: &str) -> Self {
+        let mut path = self.path.clone();
+        path.push(name.to_string());
+        Self { root: self.root.clone(), path }
+    }
+    
+    fn is_block(&self) -> bool {
+        self.path.last().is_some_and(|segment| segment.starts_with('{'))
+    }
+    
+    /// The module the scope is in, outside any blocks.
+    fn module(&self) -> Self {
+        let mut module = self.clone();
+        while module.is_block() {
+            module.path.pop();
+        }
+        module
+    }
+    
+    /// What `super` refers to.
+    fn parent(&self) -> Option<Self> {
+        let mut module = self.module();
+        module.path.pop()?;
+        Some(module.module())
+    }
+    
+    /// The scope and the blocks and module around it, innermost first.
+    fn chain(&self) -> Vec<Self> {
+        let mut chain = vec![self.clone()];
+        while let Some(scope) = chain.last().filter(|scope| scope.is_block()) {
+            let mut outer = scope.clone();
+            outer.path.pop();
+            chain.push(outer);
+        }
+        chain
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Namespace {
+    /// Items named through the module tree
+    Item,
+    /// Methods and associated items of the named type or trait
+    Member(String),
+}
+
+/// A named item, or a method implementing a trait's.
+struct Declaration {
+    scope: Scope,
+    namespace: Namespace,
+    name: String,
+    /// Visible outside its module, `pub(crate)` and `pub(super)` included
+    is_pub: bool,
+    file: PathBuf,
+    start: usize,
+    end: usize,
+}
+
+/// A `use` of one name, as the local name and the path it was imported by.
+struct Import {
+    name: String,
+    path: Vec<String>,
+}
+
+/// The items declared in a set of files and what each module imports,
+/// enough to resolve names without type information.
+#[derive(Default)]
+struct SymbolTable {
+    declarations: Vec<Declaration>,
+    /// Items by scope and name
+    items: AHashMap<(Scope, String), Vec<usize>>,
+    /// Members of any type or trait by name
+    members: AHashMap<String, Vec<usize>>,
+    imports: AHashMap<Scope, Vec<Import>>,
+    /// Module paths imported with `*`
+    globs: AHashMap<Scope, Vec<Vec<String>>>,
+    modules: AHashSet<Scope>,
+    /// Traits implemented by each type, by name
+    traits: AHashMap<String, AHashSet<String>>,
+    /// Names bound by patterns and generic parameters in each scope
+    bindings: AHashMap<Scope, AHashSet<String>>,
+    /// Names used unqualified in each scope
+    used: AHashMap<Scope, AHashSet<String>>,
+}
+
+impl SymbolTable {
+    fn target(&self, rename: &Rename) -> Option<Target> {
+        let declaration = self
+            .declarations
+            .iter()
+            .position(|declaration| declaration.file == rename.file && declaration.start == rename.offset)?;
+        let found = &self.declarations[declaration];
+        
+        // Items declared twice, say under different `cfg`s, are left alone
+        let family: AHashSet<usize> = match &found.namespace {
+            Namespace::Item => [declaration].into(),
+            Namespace::Member(_) => self.members[&found.name]
+                .iter()
+                .copied()
+                .filter(|&index| self.declarations[index].namespace == found.namespace)
+                .collect(),
+        };
+        let duplicated = found.namespace == Namespace::Item
+            && self.items[&(found.scope.clone(), found.name.clone())].len() > 1;
+        
+        Some(Target {
+            declaration,
+            name: found.name.clone(),
+            sites: family
+                .iter()
+                .map(|&index| {
+                    let declaration = &self.declarations[index];
+                    (declaration.file.clone(), declaration.start, declaration.end)
+                })
+                .collect(),
+            scopes: [found.scope.clone()].into(),
+            family,
+            in_macro: false,
+            conflict: duplicated || syn::parse_str::<syn::Ident>(&rename.to).is_err(),
         })
     }
     
-    fn to_snake_case(&self, s: &str) -> String {
-        let mut result = String::new();
-        let mut chars = s.chars().peekable();
+    /// The items `name` refers to in `scope`: declared there or imported
+    /// by name, else through a glob import or from an enclosing block.
+    fn resolve(&self, scope: &Scope, name: &str, depth: usize) -> Vec<usize> {
+        if depth > MAX_IMPORT_DEPTH {
+            return Vec::new();
+        }
         
-        while let Some(ch) = chars.next() {
-            if ch.is_uppercase() {
-                if !result.is_empty() && !result.ends_with('_') {
-                    result.push('_');
+        let mut found = self.items.get(&(scope.clone(), name.to_string())).cloned().unwrap_or_default();
+        for import in self.imports.get(scope).into_iter().flatten().filter(|import| import.name == name) {
+            if let Some((module, item)) = self.import_target(scope, &import.path, depth + 1) {
+                found.extend(self.resolve(&module, &item, depth + 1));
+            }
+        }
+        if !found.is_empty() {
+            return found;
+        }
+        
+        for glob in self.globs.get(scope).into_iter().flatten() {
+            if let Some(module) = self.resolve_module(scope, glob, depth + 1) {
+                found.extend(self.resolve(&module, name, depth + 1));
+            }
+        }
+        if found.is_empty() && scope.is_block() {
+            let mut outer = scope.clone();
+            outer.path.pop();
+            return self.resolve(&outer, name, depth);
+        }
+        found
+    }
+    
+    /// The module a path names from `scope`: through `crate`, `self` and
+    /// `super`, a child module, or a module imported by name.
+    fn resolve_module(&self, scope: &Scope, path: &[String], depth: usize) -> Option<Scope> {
+        let (first, rest) = path.split_first()?;
+        let mut module = match first.as_str() {
+            "crate" => Scope {
+                root: scope.root.clone(),
+                path: Vec::new(),
+            },
+            "self" => scope.module(),
+            "super" => scope.parent()?,
+            _ => scope.chain().into_iter().find_map(|scope| {
+                let child = scope.child(first);
+                if self.modules.contains(&child) {
+                    return Some(child);
                 }
-                result.push(ch.to_lowercase().next().unwrap_or(ch));
-            } else {
-                result.push(ch);
+                let import = self.imports.get(&scope)?.iter().find(|import| &import.name == first)?;
+                let (module, item) = self.import_target(&scope, &import.path, depth + 1)?;
+                Some(module.child(&item)).filter(|module| self.modules.contains(module))
+            })?,
+        };
+        
+        for segment in rest {
+            module = if segment == "super" { module.parent()? } else { module.child(segment) };
+        }
+        Some(module)
+    }
+    
+    /// The module and name a `use` path imports from.
+    fn import_target(&self, scope: &Scope, path: &[String], depth: usize) -> Option<(Scope, String)> {
+        if depth > MAX_IMPORT_DEPTH {
+            return None;
+        }
+        let (item, prefix) = path.split_last()?;
+        Some((self.resolve_module(scope, prefix, depth)?, item.clone()))
+    }
+    
+    /// The members `name` refers to in `Owner::name`, including those of
+    /// the traits the owner implements.
+    fn resolve_member(&self, owner: &str, name: &str) -> Vec<usize> {
+        let traits = self.traits.get(owner);
+        self.members
+            .get(name)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&index| match &self.declarations[index].namespace {
+                Namespace::Member(member_of) => member_of == owner || traits.is_some_and(|traits| traits.contains(member_of)),
+                Namespace::Item => false,
+            })
+            .collect()
+    }
+    
+    /// Whether renaming the target to `to` could change what a name refers
+    /// to, or leave two items with one name.
+    fn clashes(&self, target: &Target, to: &str) -> bool {
+        let declaration = &self.declarations[target.declaration];
+        
+        if let Namespace::Member(owner) = &declaration.namespace {
+            // Other members of the type, its traits, or the trait's implementors
+            let implementors: Vec<&String> = self
+                .traits
+                .iter()
+                .filter(|(_, traits)| traits.contains(owner))
+                .map(|(ty, _)| ty)
+                .collect();
+            return !self.resolve_member(owner, to).is_empty()
+                || implementors.into_iter().any(|ty| !self.resolve_member(ty, to).is_empty());
+        }
+        
+        if PRELUDE.contains(&to) {
+            return true;
+        }
+        let in_scope = |scope: &Scope| {
+            !self.resolve(scope, to, 0).is_empty()
+                || scope.chain().iter().any(|scope| {
+                    let has = |names: Option<&AHashSet<String>>| names.is_some_and(|names| names.contains(to));
+                    has(self.used.get(scope)) || has(self.bindings.get(scope))
+                })
+        };
+        let shadowed = |scope: &Scope| {
+            scope
+                .chain()
+                .iter()
+                .any(|scope| self.bindings.get(scope).is_some_and(|names| names.contains(&declaration.name)))
+        };
+        if target.scopes.iter().any(|scope| in_scope(scope) || shadowed(scope)) {
+            return true;
+        }
+        
+        // Modules that glob-import the item's module and already use the new name
+        self.globs.iter().any(|(scope, globs)| {
+            globs.iter().any(|glob| self.resolve_module(scope, glob, 0).as_ref() == Some(&declaration.scope))
+                && self.used.get(scope).is_some_and(|names| names.contains(to))
+        })
+    }
+}
+
+/// What a rename resolves to and where it was found.
+struct Target {
+    declaration: usize,
+    name: String,
+    /// The declaration, plus for a trait method its implementations
+    family: AHashSet<usize>,
+    sites: BTreeSet<(PathBuf, usize, usize)>,
+    /// Scopes the item is named in, where the new name must be free
+    scopes: AHashSet<Scope>,
+    in_macro: bool,
+    conflict: bool,
+}
+
+/// Keeps track of the scope being visited.
+trait ScopedVisitor {
+    fn scope_mut(&mut self) -> &mut Scope;
+    
+    /// Visits a block, in a scope of its own when it declares items.
+    fn in_block(&mut self, block: &syn::Block, visit: impl FnOnce(&mut Self)) {
+        if !block.stmts.iter().any(|stmt| matches!(stmt, syn::Stmt::Item(_))) {
+            return visit(self);
+        }
+        let id = format!("{{{}", block.brace_token.span.open().byte_range().start);
+        let inner = self.scope_mut().child(&id);
+        let outer = std::mem::replace(self.scope_mut(), inner);
+        visit(self);
+        *self.scope_mut() = outer;
+    }
+    
+    /// Visits an inline module in its own scope.
+    fn in_module(&mut self, module: &syn::ItemMod, visit: impl FnOnce(&mut Self)) {
+        let inner = self.scope_mut().child(&module.ident.unraw().to_string());
+        let outer = std::mem::replace(self.scope_mut(), inner);
+        visit(self);
+        *self.scope_mut() = outer;
+    }
+}
+
+/// The names `use` items bring into scope, with the paths they come from.
+fn flatten_use_tree(tree: &UseTree, prefix: &mut Vec<String>, imports: &mut Vec<Import>, globs: &mut Vec<Vec<String>>) {
+    match tree {
+        UseTree::Path(path) => {
+            prefix.push(path.ident.unraw().to_string());
+            flatten_use_tree(&path.tree, prefix, imports, globs);
+            prefix.pop();
+        }
+        UseTree::Name(name) if name.ident == "self" => {
+            if let Some(last) = prefix.last() {
+                imports.push(Import {
+                    name: last.clone(),
+                    path: prefix.clone(),
+                });
             }
         }
+        UseTree::Name(name) => {
+            let name = name.ident.unraw().to_string();
+            let mut path = prefix.clone();
+            path.push(name.clone());
+            imports.push(Import { name, path });
+        }
+        UseTree::Rename(rename) => {
+            let mut path = prefix.clone();
+            path.push(rename.ident.unraw().to_string());
+            imports.push(Import {
+                name: rename.rename.unraw().to_string(),
+                path,
+            });
+        }
+        UseTree::Glob(_) => globs.push(prefix.clone()),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_use_tree(tree, prefix, imports, globs);
+            }
+        }
+    }
+}
+
+/// Collects declarations, imports and local names for `SymbolTable`.
+struct DeclarationCollector<'a> {
+    symbols: &'a mut SymbolTable,
+    path: &'a Path,
+    scope: Scope,
+    /// The type or trait whose items are being visited, and for a trait
+    /// its visibility, which its items share
+    owner: Option<(String, Option<bool>)>,
+}
+
+impl DeclarationCollector<'_> {
+    fn declare(&mut self, ident: &syn::Ident, namespace: Namespace, vis: &syn::Visibility) {
+        let name = ident.unraw().to_string();
+        let range = ident.span().byte_range();
+        let index = self.symbols.declarations.len();
         
-        result
+        match &namespace {
+            Namespace::Item => self.symbols.items.entry((self.scope.clone(), name.clone())).or_default().push(index),
+            Namespace::Member(_) => self.symbols.members.entry(name.clone()).or_default().push(index),
+        }
+        self.symbols.declarations.push(Declaration {
+            scope: self.scope.clone(),
+            namespace,
+            name,
+            is_pub: !matches!(vis, syn::Visibility::Inherited),
+            file: self.path.to_path_buf(),
+            start: range.start,
+            end: range.end,
+        });
+    }
+    
+    fn declare_member(&mut self, ident: &syn::Ident, vis: &syn::Visibility) {
+        let Some((owner, trait_is_pub)) = self.owner.clone() else { return };
+        let vis = match trait_is_pub {
+            Some(true) => &syn::Visibility::Public(Default::default()),
+            Some(false) => &syn::Visibility::Inherited,
+            None => vis,
+        };
+        self.declare(ident, Namespace::Member(owner), vis);
     }
     
-    fn to_pascal_case(&self, s: &str) -> String {
-        let mut result = String::new();
-        let mut capitalize_next = true;
+    fn record_used(&mut self, tokens: &proc_macro2::TokenStream) {
+        for token in tokens.clone() {
+            match token {
+                proc_macro2::TokenTree::Ident(ident) => {
+                    self.symbols.used.entry(self.scope.clone()).or_default().insert(ident.unraw().to_string());
+                }
+                proc_macro2::TokenTree::Group(group) => self.record_used(&group.stream()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl ScopedVisitor for DeclarationCollector<'_> {
+    fn scope_mut(&mut self) -> &mut Scope {
+        &mut self.scope
+    }
+}
+
+impl<'ast> Visit<'ast> for DeclarationCollector<'_> {
+    fn visit_file(&mut self, file: &'ast SynFile) {
+        // The file's module and every module above it exist
+        let mut module = self.scope.clone();
+        loop {
+            self.symbols.modules.insert(module.clone());
+            if module.path.pop().is_none() {
+                break;
+            }
+        }
+        syn::visit::visit_file(self, file);
+    }
+    
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.declare(&item.sig.ident, Namespace::Item, &item.vis);
+        let outer = self.owner.take();
+        syn::visit::visit_item_fn(self, item);
+        self.owner = outer;
+    }
+    
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        syn::visit::visit_item_struct(self, item);
+    }
+    
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        syn::visit::visit_item_enum(self, item);
+    }
+    
+    fn visit_item_union(&mut self, item: &'ast syn::ItemUnion) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        syn::visit::visit_item_union(self, item);
+    }
+    
+    fn visit_item_type(&mut self, item: &'ast syn::ItemType) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        syn::visit::visit_item_type(self, item);
+    }
+    
+    fn visit_item_const(&mut self, item: &'ast syn::ItemConst) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        syn::visit::visit_item_const(self, item);
+    }
+    
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        syn::visit::visit_item_static(self, item);
+    }
+    
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.declare(&item.ident, Namespace::Item, &item.vis);
+        let trait_is_pub = !matches!(item.vis, syn::Visibility::Inherited);
+        let outer = self.owner.replace((item.ident.unraw().to_string(), Some(trait_is_pub)));
+        syn::visit::visit_item_trait(self, item);
+        self.owner = outer;
+    }
+    
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let self_type = self_type_name(item);
+        // Methods of a trait implementation are the trait's
+        let owner = match &item.trait_ {
+            Some((_, path, _)) => {
+                let trait_name = path.segments.last().map(|segment| segment.ident.unraw().to_string()).unwrap_or_default();
+                self.symbols.traits.entry(self_type).or_default().insert(trait_name.clone());
+                trait_name
+            }
+            None => self_type,
+        };
+        let outer = self.owner.replace((owner, None));
+        syn::visit::visit_item_impl(self, item);
+        self.owner = outer;
+    }
+    
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        self.declare_member(&item.sig.ident, &item.vis);
+        syn::visit::visit_impl_item_fn(self, item);
+    }
+    
+    fn visit_impl_item_const(&mut self, item: &'ast syn::ImplItemConst) {
+        self.declare_member(&item.ident, &item.vis);
+        syn::visit::visit_impl_item_const(self, item);
+    }
+    
+    fn visit_impl_item_type(&mut self, item: &'ast syn::ImplItemType) {
+        self.declare_member(&item.ident, &item.vis);
+        syn::visit::visit_impl_item_type(self, item);
+    }
+    
+    fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+        self.declare_member(&item.sig.ident, &syn::Visibility::Inherited);
+        syn::visit::visit_trait_item_fn(self, item);
+    }
+    
+    fn visit_trait_item_const(&mut self, item: &'ast syn::TraitItemConst) {
+        self.declare_member(&item.ident, &syn::Visibility::Inherited);
+        syn::visit::visit_trait_item_const(self, item);
+    }
+    
+    fn visit_trait_item_type(&mut self, item: &'ast syn::TraitItemType) {
+        self.declare_member(&item.ident, &syn::Visibility::Inherited);
+        syn::visit::visit_trait_item_type(self, item);
+    }
+    
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        self.symbols.modules.insert(self.scope.child(&item.ident.unraw().to_string()));
+        if item.content.is_some() {
+            let outer = self.owner.take();
+            self.in_module(item, |this| syn::visit::visit_item_mod(this, item));
+            self.owner = outer;
+        }
+    }
+    
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.in_block(block, |this| syn::visit::visit_block(this, block));
+    }
+    
+    fn visit_item_use(&mut self, item: &'ast ItemUse) {
+        let mut imports = Vec::new();
+        let mut globs = Vec::new();
+        flatten_use_tree(&item.tree, &mut Vec::new(), &mut imports, &mut globs);
+        self.symbols.imports.entry(self.scope.clone()).or_default().extend(imports);
+        self.symbols.globs.entry(self.scope.clone()).or_default().extend(globs);
+    }
+    
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.symbols.bindings.entry(self.scope.clone()).or_default().insert(pat.ident.unraw().to_string());
+        syn::visit::visit_pat_ident(self, pat);
+    }
+    
+    fn visit_type_param(&mut self, param: &'ast syn::TypeParam) {
+        self.symbols.bindings.entry(self.scope.clone()).or_default().insert(param.ident.unraw().to_string());
+        syn::visit::visit_type_param(self, param);
+    }
+    
+    fn visit_const_param(&mut self, param: &'ast syn::ConstParam) {
+        self.symbols.bindings.entry(self.scope.clone()).or_default().insert(param.ident.unraw().to_string());
+        syn::visit::visit_const_param(self, param);
+    }
+    
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if let Some(first) = path.segments.first().filter(|_| path.leading_colon.is_none()) {
+            self.symbols.used.entry(self.scope.clone()).or_default().insert(first.ident.unraw().to_string());
+        }
+        syn::visit::visit_path(self, path);
+    }
+    
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        self.record_used(&mac.tokens);
+        syn::visit::visit_macro(self, mac);
+    }
+}
+
+/// Records where the rename targets are referred to.
+struct ReferenceCollector<'a> {
+    symbols: &'a SymbolTable,
+    /// Names of the targets, to skip everything else quickly
+    names: &'a AHashSet<String>,
+    targets: &'a mut [Option<Target>],
+    path: &'a Path,
+    scope: Scope,
+    /// What `Self` names in the impl or trait being visited
+    owners: Vec<String>,
+}
+
+impl ReferenceCollector<'_> {
+    /// Records `ident` for every target among `found`, noting `scope` as
+    /// one the target is named in.
+    fn record(&mut self, ident: &proc_macro2::Ident, found: &[usize], scope: Option<Scope>, in_macro: bool) {
+        let range = ident.span().byte_range();
+        for target in self.targets.iter_mut().flatten() {
+            if !found.iter().any(|index| target.family.contains(index)) {
+                continue;
+            }
+            target.sites.insert((self.path.to_path_buf(), range.start, range.end));
+            target.scopes.extend(scope.clone());
+            target.in_macro |= in_macro;
+        }
+    }
+    
+    /// A method call, which names a target only when no other method shares
+    /// the name; when one does, renaming is left to the developer.
+    fn record_method(&mut self, ident: &proc_macro2::Ident, in_macro: bool) {
+        let Some(members) = self.symbols.members.get(&ident.unraw().to_string()) else { return };
+        let range = ident.span().byte_range();
         
-        for ch in s.chars() {
-            if ch == '_' {
-                capitalize_next = true;
-            } else if capitalize_next {
-                result.push(ch.to_uppercase().next().unwrap_or(ch));
-                capitalize_next = false;
+        for target in self.targets.iter_mut().flatten() {
+            if !members.iter().any(|index| target.family.contains(index)) {
+                continue;
+            }
+            if members.iter().all(|index| target.family.contains(index)) {
+                target.sites.insert((self.path.to_path_buf(), range.start, range.end));
+                target.in_macro |= in_macro;
             } else {
-                result.push(ch);
+                target.conflict = true;
             }
         }
+    }
+    
+    fn resolve_member(&self, owner: &str, name: &str) -> Vec<usize> {
+        match owner {
+            "Self" => self.owners.last().map(|owner| self.symbols.resolve_member(owner, name)).unwrap_or_default(),
+            _ => self.symbols.resolve_member(owner, name),
+        }
+    }
+    
+    /// Macro arguments aren't parsed, so paths and method calls are picked
+    /// out of the tokens, and finding a target among them makes the rename
+    /// unsafe.
+    fn record_tokens(&mut self, tokens: &proc_macro2::TokenStream) {
+        let mut path: Vec<String> = Vec::new();
+        let mut colons = 0;
+        let mut after_dot = false;
         
-        result
+        for token in tokens.clone() {
+            match &token {
+                proc_macro2::TokenTree::Ident(ident) => {
+                    if colons != 2 {
+                        path.clear();
+                    }
+                    let name = ident.unraw().to_string();
+                    path.push(name.clone());
+                    
+                    if !self.names.contains(&name) {
+                        // Not a target
+                    } else if after_dot {
+                        self.record_method(ident, true);
+                    } else if path.len() > 1 {
+                        let mut found = self.resolve_member(&path[path.len() - 2], &name);
+                        let module = self.symbols.resolve_module(&self.scope, &path[..path.len() - 1], 0);
+                        if let Some(module) = &module {
+                            found.extend(self.symbols.resolve(module, &name, 0));
+                        }
+                        self.record(ident, &found, module, true);
+                    } else {
+                        let found = self.symbols.resolve(&self.scope, &name, 0);
+                        self.record(ident, &found, Some(self.scope.clone()), true);
+                    }
+                    colons = 0;
+                    after_dot = false;
+                }
+                proc_macro2::TokenTree::Punct(punct) => {
+                    colons = if punct.as_char() == ':' { colons + 1 } else { 0 };
+                    after_dot = punct.as_char() == '.';
+                }
+                proc_macro2::TokenTree::Group(group) => {
+                    self.record_tokens(&group.stream());
+                    path.clear();
+                    colons = 0;
+                    after_dot = false;
+                }
+                proc_macro2::TokenTree::Literal(_) => {
+                    path.clear();
+                    colons = 0;
+                    after_dot = false;
+                }
+            }
+        }
+    }
+    
+    fn visit_use_tree_at(&mut self, tree: &UseTree, prefix: &mut Vec<String>) {
+        let ident = match tree {
+            UseTree::Path(path) => {
+                prefix.push(path.ident.unraw().to_string());
+                self.visit_use_tree_at(&path.tree, prefix);
+                prefix.pop();
+                return;
+            }
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.visit_use_tree_at(tree, prefix);
+                }
+                return;
+            }
+            UseTree::Name(name) => &name.ident,
+            UseTree::Rename(rename) => &rename.ident,
+            UseTree::Glob(_) => return,
+        };
+        if !self.names.contains(&ident.unraw().to_string()) {
+            return;
+        }
+        
+        if let Some(module) = self.symbols.resolve_module(&self.scope, prefix, 0) {
+            let found = self.symbols.resolve(&module, &ident.unraw().to_string(), 0);
+            self.record(ident, &found, Some(self.scope.clone()), false);
+        }
+    }
+}
+
+impl ScopedVisitor for ReferenceCollector<'_> {
+    fn scope_mut(&mut self) -> &mut Scope {
+        &mut self.scope
+    }
+}
+
+impl<'ast> Visit<'ast> for ReferenceCollector<'_> {
+    fn visit_item_mod(&mut self, item: &'ast syn::ItemMod) {
+        if item.content.is_some() {
+            self.in_module(item, |this| syn::visit::visit_item_mod(this, item));
+        }
+    }
+    
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.in_block(block, |this| syn::visit::visit_block(this, block));
+    }
+    
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        self.owners.push(self_type_name(item));
+        syn::visit::visit_item_impl(self, item);
+        self.owners.pop();
+    }
+    
+    fn visit_item_trait(&mut self, item: &'ast syn::ItemTrait) {
+        self.owners.push(item.ident.unraw().to_string());
+        syn::visit::visit_item_trait(self, item);
+        self.owners.pop();
+    }
+    
+    fn visit_item_use(&mut self, item: &'ast ItemUse) {
+        self.visit_use_tree_at(&item.tree, &mut Vec::new());
+    }
+    
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        let names: Vec<String> = path.segments.iter().map(|segment| segment.ident.unraw().to_string()).collect();
+        
+        for (index, segment) in path.segments.iter().enumerate() {
+            if !self.names.contains(&names[index]) {
+                continue;
+            }
+            if index == 0 {
+                if path.leading_colon.is_none() {
+                    let found = self.symbols.resolve(&self.scope, &names[0], 0);
+                    self.record(&segment.ident, &found, Some(self.scope.clone()), false);
+                }
+                continue;
+            }
+            
+            // `Type::member`, `Self::member` or `module::item`
+            let mut found = self.resolve_member(&names[index - 1], &names[index]);
+            let module = self.symbols.resolve_module(&self.scope, &names[..index], 0);
+            if let Some(module) = &module {
+                found.extend(self.symbols.resolve(module, &names[index], 0));
+            }
+            self.record(&segment.ident, &found, module, false);
+        }
+        syn::visit::visit_path(self, path);
+    }
+    
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if self.names.contains(&call.method.unraw().to_string()) {
+            self.record_method(&call.method, false);
+        }
+        syn::visit::visit_expr_method_call(self, call);
+    }
+    
+    fn visit_field_value(&mut self, field: &'ast syn::FieldValue) {
+        // `Point { x }` can't be renamed without renaming the field
+        if let (None, syn::Member::Named(ident)) = (&field.colon_token, &field.member) {
+            let found = self.symbols.resolve(&self.scope, &ident.unraw().to_string(), 0);
+            for target in self.targets.iter_mut().flatten() {
+                if found.iter().any(|index| target.family.contains(index)) {
+                    target.conflict = true;
+                }
+            }
+        }
+        syn::visit::visit_field_value(self, field);
+    }
+    
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        self.record_tokens(&mac.tokens);
+        syn::visit::visit_macro(self, mac);
     }
 }
 
@@ -346,26 +1310,63 @@ impl DocTemplateGenerator {
         Self
     }
     
-    pub fn generate_function_doc_fix(&self, fn_name: &str, location: &Location, has_params: bool, has_return: bool) -> Fix {
+    pub fn generate_function_doc_fix(
+        &self,
+        sig: &syn::Signature,
+        body: Option<&syn::Block>,
+        insert_at: usize,
+        indent: &str,
+    ) -> Fix {
+        let fn_name = sig.ident.to_string();
         let mut doc_lines = vec![
-            "/// ".to_string(),
-            format!("/// {}", self.generate_function_description(fn_name)),
+            format!("/// {}", self.generate_function_description(&fn_name)),
         ];
         
-        if has_params {
+        let params: Vec<String> = sig.inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(pat_type) => Some(self.param_name(&pat_type.pat)),
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect();
+        
+        if !params.is_empty() {
             doc_lines.push("///".to_string());
             doc_lines.push("/// # Arguments".to_string());
             doc_lines.push("///".to_string());
-            doc_lines.push("/// * `param` - Description of the parameter".to_string());
+            for param in &params {
+                doc_lines.push(format!("/// * `{}` - Description of the parameter", param));
+            }
         }
         
-        if has_return {
+        if has_return_value(&sig.output) {
             doc_lines.push("///".to_string());
             doc_lines.push("/// # Returns".to_string());
             doc_lines.push("///".to_string());
             doc_lines.push("/// Description of the return value".to_string());
         }
         
+        if returns_result(&sig.output) {
+            doc_lines.push("///".to_string());
+            doc_lines.push("/// # Errors".to_string());
+            doc_lines.push("///".to_string());
+            doc_lines.push("/// Describe the conditions under which an error is returned.".to_string());
+        }
+        
+        if body.is_some_and(block_may_panic) {
+            doc_lines.push("///".to_string());
+            doc_lines.push("/// # Panics".to_string());
+            doc_lines.push("///".to_string());
+            doc_lines.push("/// Describe the conditions under which this function panics.".to_string());
+        }
+        
+        if sig.unsafety.is_some() {
+            doc_lines.push("///".to_string());
+            doc_lines.push("/// # Safety".to_string());
+            doc_lines.push("///".to_string());
+            doc_lines.push("/// Describe the invariants the caller must uphold.".to_string());
+        }
+        
         doc_lines.push("///".to_string());
         doc_lines.push("/// # Examples".to_string());
         doc_lines.push("///".to_string());
@@ -373,32 +1374,50 @@ impl DocTemplateGenerator {
         doc_lines.push(format!("/// // Example usage of {}", fn_name));
         doc_lines.push("/// ```".to_string());
         
-        let doc_text = doc_lines.join("\n") + "\n";
-        
         Fix {
             description: format!("Add documentation template for function '{}'", fn_name),
             replacements: vec![Replacement {
-                start: location.column.saturating_sub(1),
-                end: location.column.saturating_sub(1),
-                text: doc_text,
+                start: insert_at,
+                end: insert_at,
+                text: indent_doc_lines(&doc_lines, indent),
+                file: None,
             }],
+            applicability: Applicability::Safe,
         }
     }
     
-    pub fn generate_struct_doc_fix(&self, struct_name: &str, location: &Location) -> Fix {
-        let doc_text = format!(
-            "/// {}\n///\n/// # Examples\n///\n/// ```\n/// // Example usage of {}\n/// ```\n",
-            self.generate_struct_description(struct_name),
-            struct_name
-        );
+    pub fn generate_struct_doc_fix(&self, struct_name: &str, insert_at: usize, indent: &str) -> Fix {
+        self.generate_type_doc_fix("struct", struct_name, insert_at, indent)
+    }
+    
+    /// Builds a documentation template for a struct, enum or trait.
+    pub fn generate_type_doc_fix(&self, kind: &str, type_name: &str, insert_at: usize, indent: &str) -> Fix {
+        let doc_lines = vec![
+            format!("/// {}", self.generate_struct_description(type_name)),
+            "///".to_string(),
+            "/// # Examples".to_string(),
+            "///".to_string(),
+            "/// ```".to_string(),
+            format!("/// // Example usage of {}", type_name),
+            "/// ```".to_string(),
+        ];
         
         Fix {
-            description: format!("Add documentation template for struct '{}'", struct_name),
+            description: format!("Add documentation template for {} '{}'", kind, type_name),
             replacements: vec![Replacement {
-                start: location.column.saturating_sub(1),
-                end: location.column.saturating_sub(1),
-                text: doc_text,
+                start: insert_at,
+                end: insert_at,
+                text: indent_doc_lines(&doc_lines, indent),
+                file: None,
             }],
+            applicability: Applicability::Safe,
+        }
+    }
+    
+    fn param_name(&self, pat: &syn::Pat) -> String {
+        match pat {
+            syn::Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+            other => quote::quote!(#other).to_string(),
         }
     }
     
@@ -424,6 +1443,62 @@ impl DocTemplateGenerator {
     }
 }
 
+fn indent_doc_lines(doc_lines: &[String], indent: &str) -> String {
+    doc_lines
+        .iter()
+        .map(|line| format!("{}{}\n", indent, line))
+        .collect()
+}
+
+fn has_return_value(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Default => false,
+        syn::ReturnType::Type(_, ty) => !matches!(ty.as_ref(), syn::Type::Tuple(t) if t.elems.is_empty()),
+    }
+}
+
+fn returns_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+            syn::Type::Path(type_path) => type_path.path.segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// Whether a function body contains `panic!`, `.unwrap()` or `.expect()`.
+fn block_may_panic(block: &syn::Block) -> bool {
+    struct PanicFinder {
+        found: bool,
+    }
+    
+    impl<'ast> Visit<'ast> for PanicFinder {
+        fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+            if mac.path.is_ident("panic") {
+                self.found = true;
+            }
+            syn::visit::visit_macro(self, mac);
+        }
+        
+        fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+            if call.method == "unwrap" || call.method == "expect" {
+                self.found = true;
+            }
+            syn::visit::visit_expr_method_call(self, call);
+        }
+        
+        // Nested items have their own documentation
+        fn visit_item(&mut self, _item: &'ast syn::Item) {}
+    }
+    
+    let mut finder = PanicFinder { found: false };
+    finder.visit_block(block);
+    finder.found
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -433,9 +1508,14 @@ mod tests {
         let fixer = NamingConventionFixer::new();
         
         assert_eq!(fixer.to_snake_case("CamelCase"), "camel_case");
-        assert_eq!(fixer.to_snake_case("XMLHttpRequest"), "x_m_l_http_request");
+        assert_eq!(fixer.to_snake_case("XMLHttpRequest"), "xml_http_request");
+        assert_eq!(fixer.to_snake_case("_unusedValue"), "_unused_value");
+        assert_eq!(fixer.to_snake_case("Vec3D"), "vec3_d");
         assert_eq!(fixer.to_pascal_case("snake_case"), "SnakeCase");
         assert_eq!(fixer.to_pascal_case("already_pascal"), "AlreadyPascal");
+        assert_eq!(fixer.to_pascal_case("HTTPServer"), "HttpServer");
+        assert_eq!(fixer.to_pascal_case("v1_2"), "V1_2");
+        assert_eq!(fixer.to_upper_case("maxRetries"), "MAX_RETRIES");
     }
     
     #[test]
@@ -449,10 +1529,205 @@ use std::fs;
 
 fn main() {}
 "#;
-        
+
         let organized = organizer.organize_imports(content).unwrap();
         assert!(organized.contains("use std::"));
         assert!(organized.contains("use serde::"));
         assert!(organized.contains("use crate::"));
     }
+    
+    #[test]
+    fn test_missing_docs_template_fix() {
+        use crate::rules::{MissingDocsRule, Rule, RuleContext};
+        
+        let content = r#"mod inner {
+    pub unsafe fn load(path: &str, retries: u32) -> std::io::Result<String> {
+        let data = std::fs::read_to_string(path).unwrap();
+        Ok(data)
+    }
+}
+
+#[derive(Debug)]
+pub struct Widget;
+"#;
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        MissingDocsRule::new(true).check(&mut ctx);
+        
+        let mut engine = AutoFixEngine::new();
+        let fixed = engine.apply_fixes(content, &ctx.issues).unwrap();
+        assert_eq!(engine.fixes_applied, 2);
+        assert!(fixed.contains("/// Represents a widget.\n"));
+        assert!(fixed.find("    ///").unwrap() < fixed.find("    pub unsafe fn load").unwrap());
+        assert!(fixed.find("/// Represents a widget.").unwrap() < fixed.find("#[derive(Debug)]").unwrap());
+        
+        // Nested items are indented to match, and sections follow the signature
+        let generator = DocTemplateGenerator::new();
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let syn::Item::Mod(module) = &syntax_tree.items[0] else { panic!("expected module") };
+        let Some((_, items)) = &module.content else { panic!("expected inline module") };
+        let syn::Item::Fn(func) = &items[0] else { panic!("expected function") };
+        let fix = generator.generate_function_doc_fix(&func.sig, Some(&func.block), 12, "    ");
+        let text = &fix.replacements[0].text;
+        assert!(text.lines().all(|line| line.starts_with("    ///")));
+        for section in ["* `path`", "* `retries`", "# Returns", "# Errors", "# Panics", "# Safety"] {
+            assert!(text.contains(section), "missing {}", section);
+        }
+        
+        // Without add_missing_docs the issue carries no fix
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        MissingDocsRule::new(false).check(&mut ctx);
+        assert!(ctx.issues.iter().all(|issue| issue.fix.is_none()));
+    }
+    
+    #[test]
+    fn test_workspace_rename_fix() {
+        let lib = r#"mod user;
+
+pub(crate) struct bad_name { pub value: u32, pub makeThing: u32 }
+
+fn makeThing() -> bad_name {
+    bad_name { value: 1, makeThing: 2 }
+}
+
+impl bad_name {
+    fn makeThing(&self) -> u32 { self.makeThing }
+}
+
+fn fooBar() {}
+fn foo_bar() {}
+
+fn helperFn() {}
+
+fn uses_helper() -> u32 {
+    let helper_fn = 1;
+    helperFn();
+    helper_fn
+}
+
+mod other {
+    fn makeThing() {}
+    
+    fn call() {
+        makeThing();
+        dep::makeThing();
+    }
+}
+"#;
+        let user = r#"use crate::{bad_name, makeThing};
+
+fn run(thing: &bad_name) -> u32 {
+    let made = makeThing();
+    made.value + thing.makeThing()
+}
+"#;
+        let lib_tree = syn::parse_file(lib).unwrap();
+        let user_tree = syn::parse_file(user).unwrap();
+        let files = [
+            SourceFile { path: Path::new("src/lib.rs"), syntax_tree: &lib_tree },
+            SourceFile { path: Path::new("src/user.rs"), syntax_tree: &user_tree },
+        ];
+        
+        let rename = |declaration: &str, from: &str, to: &str| Rename {
+            file: PathBuf::from("src/lib.rs"),
+            offset: lib.find(declaration).unwrap() + declaration.find(from).unwrap(),
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let renames = [
+            rename("fn makeThing() -> bad_name", "makeThing", "make_thing"),
+            rename("fn makeThing(&self)", "makeThing", "make_thing"),
+            rename("struct bad_name", "bad_name", "BadName"),
+            rename("    fn makeThing() {}", "makeThing", "make_thing"),
+            rename("fn fooBar", "fooBar", "foo_bar"),
+            rename("fn helperFn", "helperFn", "helper_fn"),
+        ];
+        
+        let fixer = NamingConventionFixer::new();
+        let fixes = fixer.create_rename_fixes(&renames, &files, true);
+        let [Some(fn_fix), Some(method_fix), Some(type_fix), Some(inner_fix), clash, shadowed] = &fixes[..] else {
+            panic!("expected the first four renames to have fixes");
+        };
+        assert_eq!(fn_fix.description, "Rename 'makeThing' to 'make_thing' (3 occurrences in 2 files)");
+        assert_eq!(method_fix.description, "Rename 'makeThing' to 'make_thing' (2 occurrences in 2 files)");
+        assert_eq!(inner_fix.description, "Rename 'makeThing' to 'make_thing' (2 occurrences in 1 files)");
+        // `foo_bar` is already declared, and `helper_fn` is a local where `helperFn` is called
+        assert!(clash.is_none() && shadowed.is_none());
+        
+        // Only private items in a fully searched crate are safe to rename,
+        // and `pub(crate)` items are not private
+        assert_eq!(fn_fix.applicability, Applicability::Safe);
+        assert_eq!(method_fix.applicability, Applicability::Safe);
+        assert_eq!(type_fix.applicability, Applicability::Unsafe);
+        let partial = fixer.create_rename_fixes(&renames[..1], &files, false);
+        assert_eq!(partial[0].as_ref().unwrap().applicability, Applicability::Unsafe);
+        
+        let apply = |path: &str, content: &str| {
+            let issues: Vec<Issue> = [fn_fix, method_fix, type_fix, inner_fix]
+                .iter()
+                .map(|fix| Issue {
+                    rule: "naming-convention".to_string(),
+                    severity: crate::rules::Severity::Warning,
+                    message: String::new(),
+                    location: crate::rules::Location { line: 1, column: 1, end_line: None, end_column: None },
+                    fix: Some(Fix {
+                        replacements: fix.replacements
+                            .iter()
+                            .filter(|r| r.file.as_deref() == Some(Path::new(path)))
+                            .cloned()
+                            .collect(),
+                        ..(*fix).clone()
+                    }),
+                    related: Vec::new(),
+                })
+                .collect();
+            AutoFixEngine::new().apply_fixes(content, &issues).unwrap()
+        };
+        
+        // Fields and other crates' items of the same name are left alone
+        let fixed_lib = apply("src/lib.rs", lib);
+        assert!(fixed_lib.contains("pub(crate) struct BadName { pub value: u32, pub makeThing: u32 }"));
+        assert!(fixed_lib.contains("fn make_thing() -> BadName {\n    BadName { value: 1, makeThing: 2 }"));
+        assert!(fixed_lib.contains("impl BadName {\n    fn make_thing(&self) -> u32 { self.makeThing }"));
+        assert!(fixed_lib.contains("fn make_thing() {}\n    \n    fn call() {\n        make_thing();\n        dep::makeThing();"));
+        assert!(fixed_lib.contains("fn fooBar() {}") && fixed_lib.contains("    helperFn();"));
+        
+        let fixed_user = apply("src/user.rs", user);
+        assert!(fixed_user.starts_with("use crate::{BadName, make_thing};"));
+        assert!(fixed_user.contains("let made = make_thing();\n    made.value + thing.make_thing()"));
+        assert!(syn::parse_file(&fixed_user).is_ok());
+        
+        // Method calls that could be another type's method aren't renamed
+        let ambiguous = format!("{}\nstruct Other;\n\nimpl Other {{\n    fn makeThing(&self) {{}}\n}}\n", lib);
+        let ambiguous_tree = syn::parse_file(&ambiguous).unwrap();
+        let files = [
+            SourceFile { path: Path::new("src/lib.rs"), syntax_tree: &ambiguous_tree },
+            SourceFile { path: Path::new("src/user.rs"), syntax_tree: &user_tree },
+        ];
+        assert!(fixer.create_rename_fixes(&renames[1..2], &files, true)[0].is_none());
+        
+        // Safe-only engines leave the public rename alone
+        let engine_config = AutoFixConfig {
+            enabled: true,
+            organize_imports: false,
+            fix_naming_conventions: true,
+            add_missing_docs: false,
+            apply_safe_fixes_only: true,
+            max_fixes_per_file: 100,
+        };
+        let recovered = fixer.rename_of(type_fix, lib).unwrap();
+        assert_eq!((recovered.from.as_str(), recovered.to.as_str()), ("bad_name", "BadName"));
+        assert_eq!((recovered.file, recovered.offset), (renames[2].file.clone(), renames[2].offset));
+        let issue = Issue {
+            rule: "naming-convention".to_string(),
+            severity: crate::rules::Severity::Warning,
+            message: String::new(),
+            location: crate::rules::Location { line: 1, column: 1, end_line: None, end_column: None },
+            fix: Some(type_fix.clone()),
+            related: Vec::new(),
+        };
+        let mut engine = AutoFixEngine::with_config(&engine_config);
+        assert_eq!(engine.apply_fixes(lib, &[issue]).unwrap(), lib);
+    }
 }
\ No newline at end of file
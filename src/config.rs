@@ -1,3 +1,4 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -6,10 +7,35 @@ pub struct Config {
     pub rules: RuleConfig,
     pub style: StyleConfig,
     pub complexity: ComplexityConfig,
+    #[serde(default)]
+    pub size: SizeConfig,
     pub cache: CacheConfig,
     pub autofix: AutoFixConfig,
     pub performance: PerformanceConfig,
     pub ignore: Vec<String>,
+    /// When non-empty, only files matching one of these globs are linted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub docs: DocsConfig,
+    #[serde(default)]
+    pub naming: NamingConfig,
+    #[serde(default)]
+    pub unwrap_usage: RuleOptions,
+    #[serde(default)]
+    pub todo_macros: RuleOptions,
+    #[serde(default)]
+    pub todo_comments: TodoCommentsConfig,
+    #[serde(default)]
+    pub may_panic: MayPanicConfig,
+    #[serde(default)]
+    pub anti_patterns: RuleOptions,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<RuleOverride>,
+    /// Directory of the `.fl.toml` this was loaded from, which globs are
+    /// relative to. `None` means the current directory.
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -20,12 +46,19 @@ pub struct RuleConfig {
     pub check_imports: bool,
     pub check_unsafe: bool,
     pub check_complexity: bool,
+    /// Defaults to on for configs written before the size rules existed
+    #[serde(default = "default_true")]
+    pub check_size: bool,
     pub check_missing_docs: bool,
     pub check_line_length: bool,
     pub check_unwrap_usage: bool,
     pub check_todo_macros: bool,
+    #[serde(default = "default_true")]
+    pub check_todo_comments: bool,
     pub check_must_use: bool,
     pub check_anti_patterns: bool,
+    #[serde(default = "default_true")]
+    pub check_may_panic: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +74,37 @@ pub struct ComplexityConfig {
     pub max_nesting: usize,
 }
 
+/// Limits for the size rules. Lines count code only, not blank lines or
+/// comments.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SizeConfig {
+    pub max_function_lines: usize,
+    /// Not counting `self`
+    pub max_params: usize,
+    pub max_fields: usize,
+    pub max_variants: usize,
+    /// Per inherent `impl` block
+    pub max_methods: usize,
+    /// Type and const parameters; lifetimes aren't counted
+    pub max_generic_params: usize,
+    pub max_file_lines: usize,
+}
+
+impl Default for SizeConfig {
+    fn default() -> Self {
+        Self {
+            max_function_lines: 100,
+            max_params: 7,
+            max_fields: 15,
+            max_variants: 30,
+            max_methods: 30,
+            max_generic_params: 5,
+            max_file_lines: 1000,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CacheConfig {
     pub enabled: bool,
@@ -69,6 +133,110 @@ pub struct PerformanceConfig {
     pub max_threads: Option<usize>,
 }
 
+/// Where each rule's documentation lives, linked from editor diagnostics.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DocsConfig {
+    /// URL with `{rule}` standing for the rule name, e.g.
+    /// `https://example.com/lints#{rule}`
+    pub url_template: Option<String>,
+    /// Per-rule URLs taking precedence over the template
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub rules: std::collections::BTreeMap<String, String>,
+}
+
+/// How `naming-convention` treats acronyms in type names.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NamingConfig {
+    /// Accept runs of capitals such as `HTTPServer`, as rustc does; when
+    /// false they must be camel case, `HttpServer`
+    pub allow_upper_case_acronyms: bool,
+}
+
+impl Default for NamingConfig {
+    fn default() -> Self {
+        Self { allow_upper_case_acronyms: true }
+    }
+}
+
+/// Options for rules that treat test code differently, e.g.
+/// `[unwrap_usage] allow_in_tests = false`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RuleOptions {
+    /// Skip `#[test]` functions, `#[cfg(test)]` modules, and files under
+    /// `tests/`, `benches/` and `examples/`
+    pub allow_in_tests: bool,
+}
+
+impl Default for RuleOptions {
+    fn default() -> Self {
+        Self { allow_in_tests: true }
+    }
+}
+
+/// Which comments `todo-comment` tracks and what it requires of them, e.g.
+/// `[todo_comments] require_issue = true`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TodoCommentsConfig {
+    /// Tags starting a tracked comment, matched in upper case
+    pub tags: Vec<String>,
+    /// Require an owner, as in `TODO(name): ...`
+    pub require_owner: bool,
+    /// Require an issue reference such as `#123` or `PROJ-123`
+    pub require_issue: bool,
+}
+
+impl Default for TodoCommentsConfig {
+    fn default() -> Self {
+        Self {
+            tags: ["TODO", "FIXME", "XXX", "HACK"].map(String::from).to_vec(),
+            require_owner: false,
+            require_issue: false,
+        }
+    }
+}
+
+/// What `may-panic` counts as panicking, besides `unwrap`, `panic!`,
+/// indexing and the like.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MayPanicConfig {
+    /// `assert!`, `assert_eq!` and `assert_ne!`
+    pub asserts: bool,
+    /// The `debug_assert` macros, which release builds leave out
+    pub debug_asserts: bool,
+}
+
+impl Default for MayPanicConfig {
+    fn default() -> Self {
+        Self {
+            asserts: true,
+            debug_asserts: false,
+        }
+    }
+}
+
+/// Rules switched off for the files matching some globs, e.g.
+/// `unwrap_usage` under `tests/**`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RuleOverride {
+    pub files: Vec<String>,
+    /// Rule names, as shown in findings
+    #[serde(default)]
+    pub disable: Vec<String>,
+}
+
+impl DocsConfig {
+    pub fn rule_url(&self, rule: &str) -> Option<String> {
+        self.rules
+            .get(rule)
+            .cloned()
+            .or_else(|| self.url_template.as_ref().map(|template| template.replace("{rule}", rule)))
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -79,12 +247,15 @@ impl Default for Config {
                 check_imports: true,
                 check_unsafe: true,
                 check_complexity: true,
+                check_size: true,
                 check_missing_docs: true,
                 check_line_length: true,
                 check_unwrap_usage: true,
                 check_todo_macros: true,
+                check_todo_comments: true,
                 check_must_use: true,
                 check_anti_patterns: true,
+                check_may_panic: true,
             },
             style: StyleConfig {
                 max_line_length: 100,
@@ -95,6 +266,7 @@ impl Default for Config {
                 max_cognitive: 15,
                 max_nesting: 5,
             },
+            size: SizeConfig::default(),
             cache: CacheConfig {
                 enabled: true,
                 cache_dir: None, // Will use system temp dir if None
@@ -122,6 +294,16 @@ impl Default for Config {
                 ".git/**".to_string(),
                 "node_modules/**".to_string(),
             ],
+            include: Vec::new(),
+            docs: DocsConfig::default(),
+            naming: NamingConfig::default(),
+            unwrap_usage: RuleOptions::default(),
+            todo_macros: RuleOptions::default(),
+            todo_comments: TodoCommentsConfig::default(),
+            may_panic: MayPanicConfig::default(),
+            anti_patterns: RuleOptions::default(),
+            overrides: Vec::new(),
+            root: None,
         }
     }
 }
@@ -131,13 +313,144 @@ impl Config {
         let config_path = path.join(".fl.toml");
         if config_path.exists() {
             if let Ok(content) = std::fs::read_to_string(&config_path) {
-                if let Ok(config) = toml::from_str(&content) {
-                    return config;
+                if let Ok(config) = toml::from_str::<Config>(&content) {
+                    return Self {
+                        root: Some(path.to_path_buf()),
+                        ..config
+                    };
                 }
             }
         }
         Self::default()
     }
+    
+    /// Config for linting `path`, a file or directory that need not exist:
+    /// the nearest `.fl.toml` at or above it, or the defaults.
+    pub fn load_for(path: &Path) -> Self {
+        match Self::config_dir_for(path) {
+            Some(config_dir) => Self::load_or_default(&config_dir),
+            None => Self::default(),
+        }
+    }
+    
+    /// Directory of the `.fl.toml` that `load_for` would read for `path`.
+    pub fn config_dir_for(path: &Path) -> Option<PathBuf> {
+        let dir = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+        let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+        Self::find_config_dir(&dir)
+    }
+    
+    /// Nearest directory at or above `start` containing a `.fl.toml`.
+    pub fn find_config_dir(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .find(|dir| dir.join(".fl.toml").is_file())
+            .map(Path::to_path_buf)
+    }
+    
+    /// Returns a copy with `overrides`, a partial config in JSON form such as
+    /// `{"rules": {"check_unwrap_usage": false}}`, merged on top.
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        let mut merged = serde_json::to_value(self)?;
+        merge_json(&mut merged, overrides);
+        Ok(Self {
+            root: self.root.clone(),
+            ..serde_json::from_value(merged)?
+        })
+    }
+    
+    /// Compiles the path-based settings for matching files against them.
+    pub fn path_matcher(&self) -> PathMatcher {
+        PathMatcher::new(self)
+    }
+}
+
+/// The `ignore` and `include` globs and per-glob rule overrides of a config,
+/// compiled. Globs use `.gitignore` syntax relative to the config's directory.
+#[derive(Debug, Clone)]
+pub struct PathMatcher {
+    root: PathBuf,
+    ignore: Gitignore,
+    include: Option<Gitignore>,
+    overrides: Vec<(Gitignore, Vec<String>)>,
+}
+
+impl PathMatcher {
+    pub fn new(config: &Config) -> Self {
+        let root = config
+            .root
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+        let root = root.canonicalize().unwrap_or(root);
+        
+        Self {
+            ignore: build_globs(&root, &config.ignore),
+            include: (!config.include.is_empty()).then(|| build_globs(&root, &config.include)),
+            overrides: config
+                .overrides
+                .iter()
+                .map(|rule_override| (build_globs(&root, &rule_override.files), rule_override.disable.clone()))
+                .collect(),
+            root,
+        }
+    }
+    
+    /// Whether `path` is left out by `ignore`, or not matched by a non-empty
+    /// `include`.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let Some(path) = self.relative(path) else { return false };
+        
+        glob_matches(&self.ignore, &path)
+            || self.include.as_ref().is_some_and(|include| !glob_matches(include, &path))
+    }
+    
+    /// Whether an override turns `rule` off for `path`.
+    pub fn is_rule_disabled(&self, path: &Path, rule: &str) -> bool {
+        if self.overrides.is_empty() {
+            return false;
+        }
+        let Some(path) = self.relative(path) else { return false };
+        
+        self.overrides
+            .iter()
+            .any(|(globs, disable)| disable.iter().any(|name| name == rule) && glob_matches(globs, &path))
+    }
+    
+    /// `path` relative to the config directory, if it lies inside it.
+    fn relative(&self, path: &Path) -> Option<PathBuf> {
+        let path = crate::walker::absolute_path(path)?;
+        path.strip_prefix(&self.root).ok().map(Path::to_path_buf)
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn build_globs(root: &Path, globs: &[String]) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for glob in globs {
+        if let Err(e) = builder.add_line(None, glob) {
+            eprintln!("Warning: Ignoring invalid glob `{}` in .fl.toml: {}", glob, e);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+fn glob_matches(globs: &Gitignore, relative_path: &Path) -> bool {
+    globs.matched_path_or_any_parents(relative_path, false).is_ignore()
+}
+
+fn merge_json(base: &mut serde_json::Value, overrides: &serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge_json(base.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overrides) => *base = overrides.clone(),
+    }
 }
 
 pub struct ConfigManager;
@@ -152,4 +465,104 @@ impl ConfigManager {
         let toml = toml::to_string_pretty(&config).unwrap();
         std::fs::write(".fl.toml", toml)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    #[test]
+    fn test_config_overrides() {
+        let config = Config::default();
+        let overridden = config
+            .with_overrides(&json!({
+                "rules": { "check_unwrap_usage": false },
+                "style": { "max_line_length": 120 },
+                "unknown": true,
+            }))
+            .unwrap();
+        
+        assert!(!overridden.rules.check_unwrap_usage);
+        assert!(overridden.rules.check_naming);
+        assert_eq!(overridden.style.max_line_length, 120);
+        assert_eq!(overridden.style.indent_size, 4);
+        
+        assert!(config.with_overrides(&json!({ "style": { "max_line_length": "wide" } })).is_err());
+    }
+    
+    #[test]
+    fn test_rule_doc_urls() {
+        let docs: DocsConfig = toml::from_str(
+            "url_template = \"https://lints.example/{rule}.html\"\n\n[rules]\nunwrap_usage = \"https://wiki.example/unwrap\"\n",
+        )
+        .unwrap();
+        
+        assert_eq!(docs.rule_url("unwrap_usage").as_deref(), Some("https://wiki.example/unwrap"));
+        assert_eq!(docs.rule_url("unused-import").as_deref(), Some("https://lints.example/unused-import.html"));
+        
+        // Configs written before the section existed still load
+        let without_docs = toml::to_string(&Config::default()).unwrap().replace("[docs]", "");
+        let config: Config = toml::from_str(&without_docs).unwrap();
+        assert_eq!(config.docs.rule_url("unused-import"), None);
+    }
+    
+    #[test]
+    fn test_size_config() {
+        let config: Config = toml::from_str(
+            &toml::to_string(&Config::default())
+                .unwrap()
+                .replace("check_size = true\n", "")
+                .replace("[size]", "[size_limits]"),
+        )
+        .unwrap();
+        assert!(config.rules.check_size);
+        assert_eq!(config.size.max_params, 7);
+        
+        let size: SizeConfig = toml::from_str("max_params = 4\n").unwrap();
+        assert_eq!((size.max_params, size.max_function_lines), (4, 100));
+    }
+    
+    #[test]
+    fn test_find_config_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let nested = temp_dir.path().join("crates/core/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(temp_dir.path().join("crates/.fl.toml"), "").unwrap();
+        
+        assert_eq!(Config::find_config_dir(&nested), Some(temp_dir.path().join("crates")));
+    }
+    
+    #[test]
+    fn test_path_matcher() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/gen")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        
+        let mut config = Config {
+            include: vec!["src/**".to_string(), "tests/**".to_string()],
+            overrides: vec![RuleOverride {
+                files: vec!["tests/**".to_string()],
+                disable: vec!["unwrap_usage".to_string()],
+            }],
+            root: Some(root.to_path_buf()),
+            ..Config::default()
+        };
+        config.ignore.extend(["*.generated.rs".to_string(), "src/gen/".to_string()]);
+        let matcher = config.path_matcher();
+        
+        assert!(!matcher.is_excluded(&root.join("src/lib.rs")));
+        assert!(matcher.is_excluded(&root.join("src/parser.generated.rs")));
+        assert!(matcher.is_excluded(&root.join("src/gen/bindings.rs")));
+        assert!(matcher.is_excluded(&root.join("build.rs")));
+        assert!(!matcher.is_excluded(&root.join("tests/integration.rs")));
+        
+        assert!(matcher.is_rule_disabled(&root.join("tests/integration.rs"), "unwrap_usage"));
+        assert!(!matcher.is_rule_disabled(&root.join("tests/integration.rs"), "line_length"));
+        assert!(!matcher.is_rule_disabled(&root.join("src/lib.rs"), "unwrap_usage"));
+        
+        // Paths outside the config directory aren't matched at all
+        assert!(!matcher.is_excluded(Path::new("/elsewhere/build.rs")));
+    }
+}
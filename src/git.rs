@@ -0,0 +1,500 @@
+use ahash::{AHashMap, AHashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Which files to take from git, compared with the working tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitScope {
+    /// Uncommitted changes, staged or not, plus untracked files
+    Changed,
+    /// Changes since the working tree's branch point from the given ref,
+    /// that is its merge base with `HEAD`, plus untracked files
+    Since(String),
+    /// Changes in the index only
+    Staged,
+    /// Untracked files that aren't ignored
+    Untracked,
+}
+
+/// The files, and optionally the lines, a git scope touches. Paths are
+/// canonical so they can be matched against walker output.
+#[derive(Debug, Default, Clone)]
+pub struct GitSelection {
+    files: AHashSet<PathBuf>,
+    /// Added or modified line ranges (1-based, inclusive) per file. Files
+    /// without an entry are new, so every line counts as changed.
+    hunks: AHashMap<PathBuf, Vec<(usize, usize)>>,
+    lines_only: bool,
+    /// Index contents per file, for the staged scope
+    staged: Option<AHashMap<PathBuf, String>>,
+}
+
+impl GitSelection {
+    /// Reads the git state of the repository containing `path`.
+    pub fn load(path: &Path, scope: &GitScope, lines_only: bool) -> io::Result<Self> {
+        Self::load_all(&[path.to_path_buf()], scope, lines_only)
+    }
+
+    /// Reads the git state of every repository containing one of `paths`.
+    pub fn load_all(paths: &[PathBuf], scope: &GitScope, lines_only: bool) -> io::Result<Self> {
+        let mut roots = Vec::new();
+        for path in paths {
+            let root = repository_root(path)?;
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+
+        let mut selection = GitSelection {
+            lines_only,
+            ..Default::default()
+        };
+        for root in roots {
+            let repository = Self::load_repository(&root, scope, lines_only)?;
+            selection.files.extend(repository.files);
+            selection.hunks.extend(repository.hunks);
+            if let Some(staged) = repository.staged {
+                selection.staged.get_or_insert_with(AHashMap::new).extend(staged);
+            }
+        }
+
+        Ok(selection)
+    }
+
+    /// Reads the git state of the repository whose top level is `root`.
+    fn load_repository(root: &Path, scope: &GitScope, lines_only: bool) -> io::Result<Self> {
+        let diff_args: Vec<String> = match scope {
+            GitScope::Changed => vec!["HEAD".to_string()],
+            // Like `<ref>...HEAD`, so commits made on the ref since branching
+            // off don't count, but compared with the working tree
+            GitScope::Since(base) => vec![git(root, &["merge-base", base, "HEAD"])?.trim_end().to_string()],
+            GitScope::Staged => vec!["--cached".to_string()],
+            GitScope::Untracked => Vec::new(),
+        };
+
+        let mut selection = GitSelection {
+            lines_only,
+            ..Default::default()
+        };
+
+        if *scope != GitScope::Untracked {
+            let mut args = vec!["diff", "--unified=0", "--no-color", "--no-ext-diff", "--no-renames"];
+            args.extend(diff_args.iter().map(String::as_str));
+            args.push("--");
+
+            for (file, hunks) in parse_diff(&git(root, &args)?) {
+                // Deleted files have nothing left to lint
+                let Ok(path) = root.join(&file).canonicalize() else { continue };
+
+                if *scope == GitScope::Staged {
+                    let source = git(root, &["show", &format!(":{}", file)])?;
+                    selection.staged.get_or_insert_with(AHashMap::new).insert(path.clone(), source);
+                }
+                selection.files.insert(path.clone());
+                selection.hunks.insert(path, hunks);
+            }
+        }
+
+        if *scope != GitScope::Staged {
+            let untracked = git(root, &["ls-files", "--others", "--exclude-standard", "-z"])?;
+            for file in untracked.split('\0').filter(|file| !file.is_empty()) {
+                if let Ok(file) = root.join(file).canonicalize() {
+                    selection.hunks.remove(&file);
+                    selection.files.insert(file);
+                }
+            }
+        }
+
+        Ok(selection)
+    }
+
+    pub fn contains_file(&self, path: &Path) -> bool {
+        path.canonicalize().is_ok_and(|path| self.files.contains(&path))
+    }
+
+    /// Whether a finding on `line` should be reported. Always true unless
+    /// the selection was loaded with `lines_only`.
+    pub fn contains_line(&self, path: &Path, line: usize) -> bool {
+        if !self.lines_only {
+            return true;
+        }
+
+        let Ok(path) = path.canonicalize() else { return false };
+        match self.hunks.get(&path) {
+            Some(hunks) => hunks.iter().any(|&(start, end)| (start..=end).contains(&line)),
+            None => self.files.contains(&path),
+        }
+    }
+
+    /// Whether findings come from the index rather than the working tree.
+    pub fn is_staged(&self) -> bool {
+        self.staged.is_some()
+    }
+
+    /// The staged contents of `path`, when linting the index.
+    pub fn staged_source(&self, path: &Path) -> Option<&str> {
+        let path = path.canonicalize().ok()?;
+        self.staged.as_ref()?.get(&path).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Adds `files` to the index of the repositories containing them.
+pub fn stage(files: &[PathBuf]) -> io::Result<()> {
+    let mut by_root: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    for file in files {
+        let root = repository_root(file)?;
+        let file = file.canonicalize()?.to_string_lossy().into_owned();
+        match by_root.iter_mut().find(|(known, _)| *known == root) {
+            Some((_, files)) => files.push(file),
+            None => by_root.push((root, vec![file])),
+        }
+    }
+
+    for (root, files) in by_root {
+        let mut args = vec!["add", "--"];
+        args.extend(files.iter().map(String::as_str));
+        git(&root, &args)?;
+    }
+    Ok(())
+}
+
+/// Marks hooks written by `install_hook`, so they can be told apart from
+/// hooks written by hand or by other tools.
+const HOOK_MARKER: &str = "# Installed by cargo-fl hook install";
+
+/// Writes a `pre-commit` hook that lints staged files, returning its path.
+/// An existing hook is only replaced when it was installed by cargo-fl or
+/// `force` is set.
+pub fn install_hook(dir: &Path, fix: bool, force: bool) -> io::Result<PathBuf> {
+    let hook = pre_commit_hook_path(dir)?;
+
+    if let Ok(existing) = std::fs::read_to_string(&hook) {
+        if !existing.contains(HOOK_MARKER) && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists; use --force to replace it", hook.display()),
+            ));
+        }
+    }
+
+    if let Some(parent) = hook.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&hook, hook_script(fix))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(hook)
+}
+
+/// Removes the `pre-commit` hook if cargo-fl installed it. Returns the
+/// removed hook's path, or `None` if there was nothing to remove.
+pub fn uninstall_hook(dir: &Path) -> io::Result<Option<PathBuf>> {
+    let hook = pre_commit_hook_path(dir)?;
+
+    match std::fs::read_to_string(&hook) {
+        Ok(existing) if existing.contains(HOOK_MARKER) => {
+            std::fs::remove_file(&hook)?;
+            Ok(Some(hook))
+        }
+        Ok(_) => Err(io::Error::other(format!(
+            "{} was not installed by cargo-fl; leaving it in place",
+            hook.display()
+        ))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn hook_script(fix: bool) -> String {
+    let fix = if fix { " --fix" } else { "" };
+    format!(
+        "#!/bin/sh\n{}\nexec cargo-fl check --staged --strict{} .\n",
+        HOOK_MARKER,
+        fix
+    )
+}
+
+fn pre_commit_hook_path(dir: &Path) -> io::Result<PathBuf> {
+    // Respects core.hooksPath and linked worktrees
+    let hooks = git(dir, &["rev-parse", "--path-format=absolute", "--git-path", "hooks"])?;
+    Ok(PathBuf::from(hooks.trim_end()).join("pre-commit"))
+}
+
+/// Top-level directory of the repository containing `path`.
+fn repository_root(path: &Path) -> io::Result<PathBuf> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(Path::new("."))
+    } else {
+        path
+    };
+    Ok(PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim_end()))
+}
+
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-c")
+        .arg("core.quotePath=false")
+        .args(args)
+        .current_dir(dir)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Added line ranges per file from `git diff --unified=0` output, keyed by
+/// repository-relative path.
+fn parse_diff(diff: &str) -> AHashMap<String, Vec<(usize, usize)>> {
+    let mut files: AHashMap<String, Vec<(usize, usize)>> = AHashMap::new();
+    let mut current = None;
+
+    for line in diff.lines() {
+        if let Some(target) = line.strip_prefix("+++ ") {
+            current = unquote_path(target).strip_prefix("b/").map(str::to_string);
+            if let Some(file) = &current {
+                files.entry(file.clone()).or_default();
+            }
+        } else if let (Some(hunk), Some(file)) = (line.strip_prefix("@@ "), &current) {
+            // @@ -old[,count] +new[,count] @@
+            let Some(added) = hunk.split_whitespace().find_map(|part| part.strip_prefix('+')) else {
+                continue;
+            };
+            let mut numbers = added.splitn(2, ',').map(|n| n.parse::<usize>().ok());
+            let start = numbers.next().flatten();
+            let count = numbers.next().unwrap_or(Some(1));
+
+            if let (Some(start), Some(count)) = (start, count) {
+                if count > 0 {
+                    files.entry(file.clone()).or_default().push((start, start + count - 1));
+                }
+            }
+        }
+    }
+
+    files
+}
+
+/// A path from a `---` or `+++` line of a diff. Git wraps paths with
+/// control characters, quotes or backslashes in double quotes with C-style
+/// escapes, and ends paths containing spaces with a tab.
+fn unquote_path(path: &str) -> String {
+    let Some(quoted) = path.strip_prefix('"').and_then(|path| path.strip_suffix('"')) else {
+        return path.strip_suffix('\t').unwrap_or(path).to_string();
+    };
+
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('a') => 0x07,
+            Some('b') => 0x08,
+            Some('t') => b'\t',
+            Some('n') => b'\n',
+            Some('v') => 0x0b,
+            Some('f') => 0x0c,
+            Some('r') => b'\r',
+            // Bytes of non-ASCII characters, unless core.quotePath is off
+            Some(digit @ '0'..='3') => {
+                let octal: String = std::iter::once(digit).chain(chars.by_ref().take(2)).collect();
+                u8::from_str_radix(&octal, 8).unwrap_or(b'?')
+            }
+            Some(other) => other as u8,
+            None => break,
+        };
+        bytes.push(byte);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_diff() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -3 +3 @@ fn main() {
+-    old();
++    new();
+@@ -10,0 +11,3 @@ fn other() {
++    a();
++    b();
++    c();
+@@ -20,2 +23,0 @@
+-    gone();
+-    gone();
+diff --git a/src/removed.rs b/src/removed.rs
+deleted file mode 100644
+--- a/src/removed.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn removed() {}
+";
+        let quoted = concat!(
+            "+++ \"b/src/tab\\there.rs\"\n",
+            "@@ -1 +1 @@\n",
+            "+++ b/src/with space.rs\t\n",
+            "@@ -2,0 +3 @@\n",
+            "+++ \"b/src/\\303\\251t\\303\\251.rs\"\n",
+            "@@ -1 +1 @@\n",
+        );
+        let files = parse_diff(&format!("{}{}", diff, quoted));
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(files["src/lib.rs"], [(3, 3), (11, 13)]);
+        assert_eq!(files["src/tab\there.rs"], [(1, 1)]);
+        assert_eq!(files["src/with space.rs"], [(3, 3)]);
+        assert_eq!(files["src/été.rs"], [(1, 1)]);
+    }
+
+    #[test]
+    fn test_selection_from_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let run = |args: &[&str]| git(root, args).unwrap();
+
+        run(&["init", "-q"]);
+        run(&["config", "user.email", "dev@example.com"]);
+        run(&["config", "user.name", "Dev"]);
+        fs::write(root.join("clean.rs"), "fn clean() {}\n").unwrap();
+        fs::write(root.join("edited.rs"), "fn a() {}\nfn b() {}\nfn c() {}\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "initial"]);
+
+        fs::write(root.join("edited.rs"), "fn a() {}\nfn changed() {}\nfn c() {}\n").unwrap();
+        fs::write(root.join("staged.rs"), "fn staged() {}\n").unwrap();
+        run(&["add", "staged.rs"]);
+        fs::write(root.join("staged.rs"), "fn staged() {}\nfn unstaged() {}\n").unwrap();
+        fs::write(root.join("new.rs"), "fn new() {}\n").unwrap();
+
+        let changed = GitSelection::load(root, &GitScope::Changed, true).unwrap();
+        assert_eq!(changed.len(), 3);
+        assert!(!changed.contains_file(&root.join("clean.rs")));
+        assert!(changed.contains_file(&root.join("new.rs")));
+        assert!(changed.contains_line(&root.join("edited.rs"), 2));
+        assert!(!changed.contains_line(&root.join("edited.rs"), 3));
+        assert!(changed.contains_line(&root.join("new.rs"), 1));
+
+        let staged = GitSelection::load(root, &GitScope::Staged, false).unwrap();
+        assert_eq!(staged.len(), 1);
+        assert!(staged.contains_file(&root.join("staged.rs")));
+        assert_eq!(staged.staged_source(&root.join("staged.rs")), Some("fn staged() {}\n"));
+        assert!(!changed.is_staged());
+
+        let untracked = GitSelection::load(root, &GitScope::Untracked, false).unwrap();
+        assert_eq!(untracked.len(), 1);
+        assert!(untracked.contains_file(&root.join("new.rs")));
+
+        let since = GitSelection::load(root, &GitScope::Since("HEAD".to_string()), false).unwrap();
+        assert_eq!(since.len(), 3);
+        assert!(since.contains_line(&root.join("edited.rs"), 3));
+    }
+
+    #[test]
+    fn test_since_compares_with_the_merge_base() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let run = |args: &[&str]| git(root, args).unwrap();
+
+        run(&["init", "-q", "-b", "main"]);
+        run(&["config", "user.email", "dev@example.com"]);
+        run(&["config", "user.name", "Dev"]);
+        fs::write(root.join("ours.rs"), "fn ours() {}\n").unwrap();
+        fs::write(root.join("theirs.rs"), "fn theirs() {}\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "initial"]);
+
+        run(&["checkout", "-q", "-b", "feature"]);
+        fs::write(root.join("ours.rs"), "fn ours() {}\nfn more() {}\n").unwrap();
+        run(&["commit", "-q", "-am", "feature work"]);
+        run(&["checkout", "-q", "main"]);
+        fs::write(root.join("theirs.rs"), "fn theirs() {}\nfn upstream() {}\n").unwrap();
+        run(&["commit", "-q", "-am", "upstream work"]);
+        run(&["checkout", "-q", "feature"]);
+
+        // Work on main since branching off isn't part of the feature
+        let since = GitSelection::load(root, &GitScope::Since("main".to_string()), false).unwrap();
+        assert_eq!(since.len(), 1);
+        assert!(since.contains_file(&root.join("ours.rs")));
+    }
+
+    #[test]
+    fn test_selection_across_repositories() {
+        let temp_dir = TempDir::new().unwrap();
+        let repositories = [temp_dir.path().join("app"), temp_dir.path().join("vendored")];
+        for root in &repositories {
+            fs::create_dir_all(root).unwrap();
+            git(root, &["init", "-q"]).unwrap();
+            fs::write(root.join("new.rs"), "fn new() {}\n").unwrap();
+        }
+
+        let selection = GitSelection::load_all(&repositories, &GitScope::Untracked, false).unwrap();
+        assert_eq!(selection.len(), 2);
+        assert!(repositories.iter().all(|root| selection.contains_file(&root.join("new.rs"))));
+
+        stage(&[repositories[0].join("new.rs"), repositories[1].join("new.rs")]).unwrap();
+        for root in &repositories {
+            let staged = GitSelection::load(root, &GitScope::Staged, false).unwrap();
+            assert!(staged.contains_file(&root.join("new.rs")));
+        }
+    }
+
+    #[test]
+    fn test_hook_install_and_uninstall() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        git(root, &["init", "-q"]).unwrap();
+        let hook = root.join(".git/hooks/pre-commit");
+
+        assert_eq!(install_hook(root, true, false).unwrap().canonicalize().unwrap(), hook.canonicalize().unwrap());
+        let script = fs::read_to_string(&hook).unwrap();
+        assert!(script.contains("cargo-fl check --staged --strict --fix"));
+
+        // Reinstalling over our own hook is fine
+        install_hook(root, false, false).unwrap();
+        assert!(!fs::read_to_string(&hook).unwrap().contains("--fix"));
+
+        assert!(uninstall_hook(root).unwrap().is_some());
+        assert!(!hook.exists());
+        assert!(uninstall_hook(root).unwrap().is_none());
+
+        // Hooks from elsewhere are left alone unless forced
+        fs::write(&hook, "#!/bin/sh\nmake lint\n").unwrap();
+        assert!(install_hook(root, false, false).is_err());
+        assert!(uninstall_hook(root).is_err());
+        install_hook(root, false, true).unwrap();
+        assert!(fs::read_to_string(&hook).unwrap().contains(HOOK_MARKER));
+    }
+}
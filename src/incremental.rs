@@ -1,6 +1,6 @@
 use crate::cache::{AnalysisCache, FileMetadata};
-use crate::rules::{Issue, Rule, RuleContext};
-use crate::config::Config;
+use crate::rules::{rules_allowed_in_tests, Issue, Rule, RuleContext};
+use crate::config::{Config, PathMatcher};
 use ahash::AHashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -10,6 +10,7 @@ pub struct IncrementalAnalyzer {
     config: Arc<Config>,
     rules: Vec<Box<dyn Rule>>,
     cache: AnalysisCache,
+    path_matcher: PathMatcher,
 }
 
 #[derive(Debug)]
@@ -35,12 +36,24 @@ impl IncrementalAnalyzer {
         let cache = AnalysisCache::new(cache_dir);
         
         Self {
+            path_matcher: config.path_matcher(),
             config: Arc::new(config),
             rules,
             cache,
         }
     }
     
+    /// An analyzer whose cache lives only in memory, for long-running hosts
+    /// that shouldn't share results with runs using a different config.
+    pub fn in_memory(config: Config) -> Self {
+        Self {
+            rules: crate::rules::get_enabled_rules(&config),
+            path_matcher: config.path_matcher(),
+            config: Arc::new(config),
+            cache: AnalysisCache::default(),
+        }
+    }
+    
     pub fn analyze_files(&mut self, files: Vec<PathBuf>) -> IncrementalResults {
         // Clean up stale cache entries first
         self.cache.cleanup_stale_entries();
@@ -120,14 +133,17 @@ impl IncrementalAnalyzer {
             file_path.to_path_buf(),
             content,
             syntax_tree,
-        );
+        )
+        .allow_in_tests(&rules_allowed_in_tests(&self.config));
         
-        // Apply each rule
+        // Apply each rule not switched off for this path
         for rule in &self.rules {
-            rule.check(&mut ctx);
+            if !self.path_matcher.is_rule_disabled(file_path, rule.name()) {
+                rule.check(&mut ctx);
+            }
         }
         
-        Ok(ctx.issues)
+        Ok(ctx.into_issues())
     }
     
     pub fn invalidate_file(&mut self, path: &Path) {
@@ -0,0 +1,222 @@
+use crate::ast_cache::read_rust_file;
+use crate::rules::{Applicability, Fix, Issue};
+use ahash::{AHashMap, AHashSet};
+use colored::*;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// What the user chose to do with a proposed fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewDecision {
+    Accept,
+    Skip,
+    AcceptRule,
+    Quit,
+}
+
+/// Walks the user through proposed fixes one at a time, showing a diff hunk
+/// for each and asking whether to apply it.
+pub struct InteractiveReviewer<R, W> {
+    input: R,
+    output: W,
+    accepted_rules: AHashSet<String>,
+    sources: AHashMap<PathBuf, String>,
+    quit: bool,
+    pub accepted: usize,
+    pub skipped: usize,
+}
+
+impl InteractiveReviewer<io::StdinLock<'static>, io::Stdout> {
+    pub fn stdio() -> Self {
+        Self::new(io::stdin().lock(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> InteractiveReviewer<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            accepted_rules: AHashSet::new(),
+            sources: AHashMap::new(),
+            quit: false,
+            accepted: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Returns whether the fix attached to `issue` should be applied.
+    pub fn review(&mut self, file: &Path, issue: &Issue) -> bool {
+        let decision = if self.quit {
+            ReviewDecision::Skip
+        } else if self.accepted_rules.contains(&issue.rule) {
+            ReviewDecision::Accept
+        } else {
+            match issue.fix.as_ref() {
+                Some(fix) => self.ask(file, issue, fix).unwrap_or(ReviewDecision::Quit),
+                None => ReviewDecision::Skip,
+            }
+        };
+
+        match decision {
+            ReviewDecision::Accept => {}
+            ReviewDecision::AcceptRule => {
+                self.accepted_rules.insert(issue.rule.clone());
+            }
+            ReviewDecision::Skip => {
+                self.skipped += 1;
+                return false;
+            }
+            ReviewDecision::Quit => {
+                self.quit = true;
+                self.skipped += 1;
+                return false;
+            }
+        }
+
+        self.accepted += 1;
+        true
+    }
+
+    fn ask(&mut self, file: &Path, issue: &Issue, fix: &Fix) -> io::Result<ReviewDecision> {
+        let diff = self.render_diff(file, fix);
+        let unsafe_note = if fix.applicability == Applicability::Unsafe {
+            format!(" {}", "(unsafe)".red())
+        } else {
+            String::new()
+        };
+
+        writeln!(
+            self.output,
+            "\n{}:{}:{} [{}] {}",
+            file.display().to_string().bold(),
+            issue.location.line,
+            issue.location.column,
+            issue.rule.dimmed(),
+            issue.message
+        )?;
+        writeln!(self.output, "  {} {}{}", "fix:".cyan().bold(), fix.description, unsafe_note)?;
+        write!(self.output, "{}", diff)?;
+
+        loop {
+            write!(
+                self.output,
+                "Apply this fix? [y]es, [n]o, [a]ll for {}, [q]uit: ",
+                issue.rule
+            )?;
+            self.output.flush()?;
+
+            let mut answer = String::new();
+            if self.input.read_line(&mut answer)? == 0 {
+                return Ok(ReviewDecision::Quit);
+            }
+
+            match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => return Ok(ReviewDecision::Accept),
+                "n" | "no" => return Ok(ReviewDecision::Skip),
+                "a" | "all" => return Ok(ReviewDecision::AcceptRule),
+                "q" | "quit" => return Ok(ReviewDecision::Quit),
+                _ => writeln!(self.output, "Please answer y, n, a or q")?,
+            }
+        }
+    }
+
+    /// Renders one hunk per replacement: the touched lines before and after.
+    fn render_diff(&mut self, file: &Path, fix: &Fix) -> String {
+        let mut replacements: Vec<_> = fix.replacements
+            .iter()
+            .map(|r| (r.file.clone().unwrap_or_else(|| file.to_path_buf()), r))
+            .collect();
+        replacements.sort_by(|(a, ra), (b, rb)| a.cmp(b).then(ra.start.cmp(&rb.start)));
+
+        let mut diff = String::new();
+
+        for (path, replacement) in replacements {
+            let Some(content) = self.source(&path) else { continue };
+            if replacement.end > content.len() || replacement.start > replacement.end {
+                continue;
+            }
+
+            let hunk_start = content[..replacement.start].rfind('\n').map_or(0, |i| i + 1);
+            let hunk_end = content[replacement.end..]
+                .find('\n')
+                .map_or(content.len(), |i| replacement.end + i);
+            let line = content[..hunk_start].matches('\n').count() + 1;
+
+            let old = &content[hunk_start..hunk_end];
+            let new = format!(
+                "{}{}{}",
+                &content[hunk_start..replacement.start],
+                replacement.text,
+                &content[replacement.end..hunk_end]
+            );
+
+            diff.push_str(&format!("  {} {}:{}\n", "-->".blue(), path.display(), line));
+            for old_line in old.lines() {
+                diff.push_str(&format!("  {}\n", format!("-{}", old_line).red()));
+            }
+            for new_line in new.lines() {
+                diff.push_str(&format!("  {}\n", format!("+{}", new_line).green()));
+            }
+        }
+
+        diff
+    }
+
+    fn source(&mut self, path: &Path) -> Option<&str> {
+        if !self.sources.contains_key(path) {
+            let content = read_rust_file(path).ok()?;
+            self.sources.insert(path.to_path_buf(), content);
+        }
+        self.sources.get(path).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Location, Replacement, Severity};
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    fn issue(rule: &str, start: usize, end: usize, text: &str) -> Issue {
+        Issue {
+            rule: rule.to_string(),
+            severity: Severity::Warning,
+            message: format!("{} issue", rule),
+            location: Location { line: 1, column: 1, end_line: None, end_column: None },
+            fix: Some(Fix {
+                description: "Replace".to_string(),
+                replacements: vec![Replacement { start, end, text: text.to_string(), file: None }],
+                applicability: Applicability::Safe,
+            }),
+            related: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_review_decisions() {
+        colored::control::set_override(false);
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}\nfn b() {}\n").unwrap();
+
+        let input = Cursor::new("maybe\nn\na\nq\n");
+        let mut output = Vec::new();
+        let mut reviewer = InteractiveReviewer::new(input, &mut output);
+
+        assert!(!reviewer.review(&file, &issue("first", 3, 4, "x")));
+        assert!(reviewer.review(&file, &issue("second", 13, 14, "y")));
+        // Accepted for the whole rule, so no prompt
+        assert!(reviewer.review(&file, &issue("second", 3, 4, "z")));
+        assert!(!reviewer.review(&file, &issue("third", 3, 4, "w")));
+        // After quitting everything else is skipped
+        assert!(!reviewer.review(&file, &issue("second", 3, 4, "v")));
+        assert_eq!((reviewer.accepted, reviewer.skipped), (2, 3));
+
+        let shown = String::from_utf8(output).unwrap();
+        assert!(shown.contains("Please answer y, n, a or q"));
+        assert!(shown.contains("-fn b() {}\n  +fn y() {}"));
+        assert_eq!(shown.matches("Apply this fix?").count(), 4);
+    }
+}
@@ -14,6 +14,11 @@ pub mod cache;
 pub mod incremental;
 pub mod ast_cache;
 pub mod autofix;
+pub mod interactive;
+pub mod git;
+pub mod watch;
+pub mod metrics;
+pub mod todos;
 
 pub use analyzer::{Analyzer, AnalysisResults};
 pub use config::{Config, ConfigManager};
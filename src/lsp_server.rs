@@ -1,78 +1,751 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
 use cargo_fl::analyzer::Analyzer;
-use cargo_fl::config::Config;
-use cargo_fl::rules::Severity;
+use cargo_fl::autofix::AutoFixEngine;
+use cargo_fl::config::{Config, DocsConfig};
+use cargo_fl::incremental::IncrementalAnalyzer;
+use cargo_fl::rules::{function_metrics, suppression_comment, Applicability, Fix, FunctionMetrics, Issue, Severity};
+use cargo_fl::walker::RustFileWalker;
 
+/// Code action kind for applying every safe fix, e.g. on save.
+const SOURCE_FIX_ALL_CARGO_FL: CodeActionKind = CodeActionKind::new("source.fixAll.cargo-fl");
+
+/// How long a document has to stay unchanged before it is re-linted.
+const LINT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Command linting every file in the workspace folders.
+const LINT_WORKSPACE_COMMAND: &str = "cargo-fl-lsp.lintWorkspace";
+
+/// Command of the complexity code lenses. They are informational, so it does nothing.
+const SHOW_METRICS_COMMAND: &str = "cargo-fl-lsp.showMetrics";
+
+/// Files linted per background batch of a workspace diagnostic pull.
+const WORKSPACE_LINT_BATCH: usize = 64;
+
+/// An open document as the client currently sees it.
+struct Document {
+    text: String,
+    version: i32,
+}
+
+/// Configuration and analyzer for one workspace folder, or for the project
+/// around a file opened outside of every folder.
+struct Workspace {
+    root: PathBuf,
+    config: Config,
+    analyzer: Analyzer,
+    /// Lints files on disk for workspace diagnostics
+    incremental: Arc<std::sync::Mutex<IncrementalAnalyzer>>,
+}
+
+impl Workspace {
+    /// Loads the `.fl.toml` governing `root` with the editor settings merged on top.
+    fn load(root: PathBuf, settings: &serde_json::Value) -> Self {
+        let config_dir = Config::find_config_dir(&root).unwrap_or_else(|| root.clone());
+        let file_config = Config::load_or_default(&config_dir);
+        // Settings that don't fit the config schema are ignored rather than
+        // leaving the workspace unlinted
+        let config = file_config.with_overrides(settings).unwrap_or(file_config);
+        
+        Self {
+            root,
+            analyzer: Analyzer::new(config.clone()),
+            incremental: Arc::new(std::sync::Mutex::new(IncrementalAnalyzer::in_memory(config.clone()))),
+            config,
+        }
+    }
+}
+
+/// Clones share their state, so background tasks can lint with a clone.
+#[derive(Clone)]
 pub struct Backend {
     client: Client,
-    analyzer: Mutex<Analyzer>,
-    config: Mutex<Config>,
+    workspaces: Arc<Mutex<Vec<Workspace>>>,
+    /// The `cargo-fl` section of the editor settings
+    settings: Arc<std::sync::Mutex<serde_json::Value>>,
+    documents: Arc<std::sync::Mutex<HashMap<Url, Document>>>,
+    /// Debounced lints waiting for the user to pause typing, by document
+    pending_lints: Arc<std::sync::Mutex<HashMap<Url, JoinHandle<()>>>>,
+    /// Whether the client pulls diagnostics instead of having them pushed
+    pull_diagnostics: Arc<AtomicBool>,
+    /// Bumped whenever configuration changes, invalidating every result ID
+    config_generation: Arc<AtomicU64>,
+    /// Wakes workspace diagnostic pulls waiting for something to change
+    workspace_changed: Arc<Notify>,
 }
 
 impl Backend {
     pub fn new(client: Client) -> Self {
-        let config = Config::load_or_default(&PathBuf::from("."));
-        let analyzer = Analyzer::new(config.clone());
-        
         Self {
             client,
-            analyzer: Mutex::new(analyzer),
-            config: Mutex::new(config),
+            workspaces: Arc::new(Mutex::new(Vec::new())),
+            settings: Arc::new(std::sync::Mutex::new(serde_json::Value::Null)),
+            documents: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            pending_lints: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            pull_diagnostics: Arc::new(AtomicBool::new(false)),
+            config_generation: Arc::new(AtomicU64::new(0)),
+            workspace_changed: Arc::new(Notify::new()),
         }
     }
 
-    async fn lint_document(&self, uri: &Url) -> Result<Vec<Diagnostic>> {
-        let path = uri.to_file_path().map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?;
+    /// Identifies the diagnostics of `content` under the current configuration.
+    fn result_id(&self, content: &str) -> String {
+        content_result_id(self.config_generation.load(Ordering::Relaxed), content)
+    }
+
+    fn settings(&self) -> serde_json::Value {
+        self.settings.lock().unwrap().clone()
+    }
+
+    fn enabled(&self) -> bool {
+        let settings = self.settings.lock().unwrap();
+        settings.get("enable").and_then(serde_json::Value::as_bool).unwrap_or(true)
+    }
+
+    /// The workspace `path` belongs to. Files outside of every folder get a
+    /// workspace rooted at the nearest `.fl.toml`, or at their own directory.
+    fn workspace_for<'a>(&self, workspaces: &'a mut Vec<Workspace>, path: &Path) -> &'a Workspace {
+        let roots: Vec<&Path> = workspaces.iter().map(|workspace| workspace.root.as_path()).collect();
+        let index = match innermost_root(&roots, path) {
+            Some(index) => index,
+            None => {
+                let dir = path.parent().unwrap_or(path);
+                let root = Config::find_config_dir(dir).unwrap_or_else(|| dir.to_path_buf());
+                workspaces.push(Workspace::load(root, &self.settings()));
+                workspaces.len() - 1
+            }
+        };
+        &workspaces[index]
+    }
+
+    /// Lints `content` with the configuration of the workspace containing `path`.
+    async fn analyze_source(&self, path: &Path, content: &str) -> Option<Vec<Issue>> {
+        if !self.enabled() {
+            return Some(Vec::new());
+        }
+        
+        let mut workspaces = self.workspaces.lock().await;
+        let analyzer = &self.workspace_for(&mut workspaces, path).analyzer;
+        // Files the CLI would skip get no diagnostics either
+        if analyzer.is_ignored(path) {
+            return Some(Vec::new());
+        }
+        analyzer.analyze_source(path, content)
+    }
+
+    async fn config_for(&self, path: &Path) -> Config {
+        let mut workspaces = self.workspaces.lock().await;
+        self.workspace_for(&mut workspaces, path).config.clone()
+    }
+
+    /// Reloads every workspace's configuration and re-lints the open documents.
+    async fn reload(&self) {
+        let settings = self.settings();
+        {
+            let mut workspaces = self.workspaces.lock().await;
+            for workspace in workspaces.iter_mut() {
+                *workspace = Workspace::load(workspace.root.clone(), &settings);
+            }
+        }
+        
+        self.refresh_diagnostics().await;
+    }
+
+    /// Brings the client's diagnostics up to date after a configuration change.
+    async fn refresh_diagnostics(&self) {
+        self.config_generation.fetch_add(1, Ordering::Relaxed);
+        self.workspace_changed.notify_waiters();
+        
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            // Not every client supports refreshing; they re-pull on their own eventually
+            let _ = self.client.workspace_diagnostic_refresh().await;
+            return;
+        }
+        
+        let uris: Vec<Url> = self.documents.lock().unwrap().keys().cloned().collect();
+        for uri in uris {
+            self.lint_and_publish(&uri).await;
+        }
+    }
+
+    /// Lints every Rust file in the workspace folders except open documents,
+    /// which the client pulls separately. Files whose content still matches
+    /// their previous result ID are reported unchanged without being linted.
+    /// Files are read and linted in batches off the async runtime so requests
+    /// for open documents aren't held up.
+    async fn workspace_report(
+        &self,
+        previous_result_ids: HashMap<Url, String>,
+        progress: &WorkDone,
+    ) -> WorkspaceDiagnosticReport {
+        let (roots, analyzers): (Vec<(PathBuf, RustFileWalker)>, Vec<_>) = self
+            .workspaces
+            .lock()
+            .await
+            .iter()
+            .map(|workspace| {
+                let analyzer = (workspace.incremental.clone(), workspace.config.docs.clone());
+                let walker = RustFileWalker::with_config(&workspace.config);
+                ((workspace.root.clone(), walker), analyzer)
+            })
+            .unzip();
+        let open: HashSet<Url> = self.documents.lock().unwrap().keys().cloned().collect();
         
-        let analyzer = self.analyzer.lock().await;
-        let results = analyzer.analyze_file(&path);
+        // Each file belongs to the innermost folder containing it
+        let root_paths: Vec<&Path> = roots.iter().map(|(root, _)| root.as_path()).collect();
+        let mut files_by_workspace: Vec<Vec<PathBuf>> = vec![Vec::new(); roots.len()];
+        for (index, (root, walker)) in roots.iter().enumerate() {
+            for file in walker.walk(root) {
+                let open = Url::from_file_path(&file).is_ok_and(|uri| open.contains(&uri));
+                if !open && innermost_root(&root_paths, &file) == Some(index) {
+                    files_by_workspace[index].push(file);
+                }
+            }
+        }
         
-        let mut diagnostics = Vec::new();
+        let total: usize = files_by_workspace.iter().map(Vec::len).sum();
+        let generation = self.config_generation.load(Ordering::Relaxed);
+        let mut done = 0;
+        let mut items = Vec::new();
         
-        if let Some(issues) = results.file_issues.get(&path) {
-            for issue in issues {
-                let diagnostic = Diagnostic {
-                    range: Range {
-                        start: Position {
-                            line: issue.location.line.saturating_sub(1) as u32,
-                            character: issue.location.column.saturating_sub(1) as u32,
+        for (files, (analyzer, docs)) in files_by_workspace.into_iter().zip(analyzers) {
+            for batch in files.chunks(WORKSPACE_LINT_BATCH) {
+                let batch: Vec<(PathBuf, Url, Option<String>)> = batch
+                    .iter()
+                    .filter_map(|file| {
+                        let uri = Url::from_file_path(file).ok()?;
+                        let previous = previous_result_ids.get(&uri).cloned();
+                        Some((file.clone(), uri, previous))
+                    })
+                    .collect();
+                let analyzer = analyzer.clone();
+                let linted = tokio::task::spawn_blocking(move || {
+                    let mut unchanged = Vec::new();
+                    let mut changed = Vec::new();
+                    for (file, uri, previous) in batch {
+                        let content = std::fs::read_to_string(&file).unwrap_or_default();
+                        let result_id = content_result_id(generation, &content);
+                        if previous.as_ref() == Some(&result_id) {
+                            unchanged.push((uri, result_id));
+                        } else {
+                            changed.push((file, uri, content, result_id));
+                        }
+                    }
+                    
+                    let paths: Vec<PathBuf> = changed.iter().map(|(file, ..)| file.clone()).collect();
+                    let issues = if paths.is_empty() {
+                        Default::default()
+                    } else {
+                        analyzer.lock().unwrap().analyze_files(paths).all_issues()
+                    };
+                    (unchanged, changed, issues)
+                })
+                .await;
+                let Ok((unchanged, changed, mut issues)) = linted else { continue };
+                
+                for (uri, result_id) in unchanged {
+                    items.push(WorkspaceDocumentDiagnosticReport::Unchanged(
+                        WorkspaceUnchangedDocumentDiagnosticReport {
+                            uri,
+                            version: None,
+                            unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport { result_id },
                         },
-                        end: Position {
-                            line: issue.location.line.saturating_sub(1) as u32,
-                            character: issue.location.end_column.unwrap_or(issue.location.column + 1).saturating_sub(1) as u32,
+                    ));
+                }
+                for (file, uri, content, result_id) in changed {
+                    let diagnostics = issues
+                        .remove(&file)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|issue| to_diagnostic(&uri, &content, issue, &docs))
+                        .collect();
+                    items.push(WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                            result_id: Some(result_id),
+                            items: diagnostics,
                         },
-                    },
-                    severity: Some(match issue.severity {
-                        Severity::Error => DiagnosticSeverity::ERROR,
-                        Severity::Warning => DiagnosticSeverity::WARNING,
-                        Severity::Info => DiagnosticSeverity::INFORMATION,
-                    }),
-                    code: Some(NumberOrString::String(issue.rule.to_string())),
-                    source: Some("cargo-fl".to_string()),
-                    message: issue.message.clone(),
-                    related_information: None,
-                    tags: None,
-                    code_description: None,
-                    data: None,
-                };
-                diagnostics.push(diagnostic);
+                    }));
+                }
+                
+                done += WORKSPACE_LINT_BATCH.min(total - done);
+                progress.report(format!("{}/{} files", done, total), (done * 100 / total.max(1)) as u32).await;
             }
         }
         
-        Ok(diagnostics)
+        WorkspaceDiagnosticReport { items }
+    }
+
+    /// Asks the client for the `cargo-fl` settings section.
+    async fn fetch_settings(&self) -> Option<serde_json::Value> {
+        let item = ConfigurationItem {
+            scope_uri: None,
+            section: Some("cargo-fl".to_string()),
+        };
+        self.client.configuration(vec![item]).await.ok()?.into_iter().next()
+    }
+
+    /// The text of an open document, or the file on disk otherwise.
+    fn document_text(&self, uri: &Url, path: &Path) -> String {
+        match self.documents.lock().unwrap().get(uri) {
+            Some(document) => document.text.clone(),
+            None => std::fs::read_to_string(path).unwrap_or_default(),
+        }
+    }
+
+    fn document_version(&self, uri: &Url) -> Option<i32> {
+        self.documents.lock().unwrap().get(uri).map(|document| document.version)
+    }
+
+    /// Lints a document, returning its path, the content the issue offsets
+    /// refer to, and the issues. Unparsable content yields no issues.
+    async fn analyze_document(&self, uri: &Url) -> Result<(PathBuf, String, Vec<Issue>)> {
+        let path = uri.to_file_path().map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?;
+        let content = self.document_text(uri, &path);
+        let issues = self.analyze_source(&path, &content).await.unwrap_or_default();
+        
+        Ok((path, content, issues))
+    }
+
+    /// Lints a document and publishes the diagnostics. While the document
+    /// doesn't parse the previous diagnostics are left in place.
+    async fn lint_and_publish(&self, uri: &Url) {
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            return;
+        }
+        
+        let Ok(path) = uri.to_file_path() else { return };
+        let version = self.document_version(uri);
+        let content = self.document_text(uri, &path);
+        
+        let Some(issues) = self.analyze_source(&path, &content).await else { return };
+        
+        // A newer edit arrived while linting; its own lint will publish
+        if self.document_version(uri) != version {
+            return;
+        }
+        
+        let docs = self.config_for(&path).await.docs;
+        let diagnostics = issues.iter().map(|issue| to_diagnostic(uri, &content, issue, &docs)).collect();
+        self.client.publish_diagnostics(uri.clone(), diagnostics, version).await;
+    }
+    
+    /// Builds a single edit applying every local fix accepted by `filter`,
+    /// or `None` when nothing would change.
+    async fn fix_all_edit(
+        &self,
+        uri: &Url,
+        path: &Path,
+        content: &str,
+        issues: &[Issue],
+        safe_fixes_only: bool,
+        filter: impl Fn(&Issue) -> bool,
+    ) -> Option<WorkspaceEdit> {
+        // Fixes that reach into other files can't be applied as part of this document
+        let local: Vec<Issue> = issues
+            .iter()
+            .filter(|issue| filter(issue))
+            .filter(|issue| {
+                issue.fix.as_ref().is_some_and(|fix| {
+                    fix.replacements.iter().all(|r| r.file.as_deref().is_none_or(|file| file == path))
+                })
+            })
+            .cloned()
+            .collect();
+        
+        let mut engine = AutoFixEngine::with_config(&self.config_for(path).await.autofix);
+        engine.safe_fixes_only |= safe_fixes_only;
+        
+        let fixed = engine.apply_fixes(content, &local).ok()?;
+        if fixed == content {
+            return None;
+        }
+        
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone(), vec![minimal_text_edit(content, &fixed)]);
+        Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        })
+    }
+}
+
+/// `$/progress` reporting for a long-running request. Reports are dropped when
+/// the client provided no token and refused to create one.
+struct WorkDone {
+    client: Client,
+    token: Option<ProgressToken>,
+}
+
+impl WorkDone {
+    async fn begin(client: &Client, token: Option<ProgressToken>, title: &str) -> Self {
+        let token = match token {
+            Some(token) => Some(token),
+            None => {
+                let token = ProgressToken::String(format!("cargo-fl/{}", title));
+                let params = WorkDoneProgressCreateParams { token: token.clone() };
+                client
+                    .send_request::<request::WorkDoneProgressCreate>(params)
+                    .await
+                    .ok()
+                    .map(|_| token)
+            }
+        };
+        
+        let work_done = Self { client: client.clone(), token };
+        work_done
+            .send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            }))
+            .await;
+        work_done
+    }
+
+    async fn report(&self, message: String, percentage: u32) {
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message: Some(message),
+            percentage: Some(percentage),
+        }))
+        .await;
+    }
+
+    async fn end(self, message: String) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd { message: Some(message) })).await;
+    }
+
+    async fn send(&self, progress: WorkDoneProgress) {
+        if let Some(token) = &self.token {
+            self.client
+                .send_notification::<notification::Progress>(ProgressParams {
+                    token: token.clone(),
+                    value: ProgressParamsValue::WorkDone(progress),
+                })
+                .await;
+        }
+    }
+}
+
+/// Complexity metrics for each function in `content`, paired with the range of
+/// its name. Empty when the content doesn't parse.
+fn function_metric_ranges(content: &str) -> Vec<(Range, FunctionMetrics)> {
+    let Ok(file) = syn::parse_file(content) else { return Vec::new() };
+    
+    function_metrics(&file)
+        .into_iter()
+        .map(|metrics| {
+            let line = metrics.line.saturating_sub(1);
+            let text = line_text(content, line);
+            let start = utf16_column(text, metrics.column);
+            let name_len = metrics.name.rsplit("::").next().unwrap_or("").encode_utf16().count() as u32;
+            let range = Range {
+                start: Position { line: line as u32, character: start },
+                end: Position { line: line as u32, character: start + name_len },
+            };
+            (range, metrics)
+        })
+        .collect()
+}
+
+/// Number of issues reported within the lines of a function.
+fn findings_in(metrics: &FunctionMetrics, issues: &[Issue]) -> usize {
+    issues
+        .iter()
+        .filter(|issue| (metrics.line..=metrics.end_line).contains(&issue.location.line))
+        .count()
+}
+
+fn metrics_hover(metrics: &FunctionMetrics, findings: usize) -> String {
+    format!(
+        "**{}**\n\n| Metric | Value |\n| --- | --- |\n\
+         | Cyclomatic complexity | {} |\n| Cognitive complexity | {} |\n\
+         | Max nesting depth | {} |\n| Lint findings | {} |",
+        metrics.name, metrics.cyclomatic, metrics.cognitive, metrics.max_nesting, findings
+    )
+}
+
+fn metrics_lens_title(metrics: &FunctionMetrics, findings: usize) -> String {
+    let findings = match findings {
+        0 => "no findings".to_string(),
+        1 => "1 finding".to_string(),
+        n => format!("{} findings", n),
+    };
+    format!(
+        "cyclomatic {} · cognitive {} · nesting {} · {}",
+        metrics.cyclomatic, metrics.cognitive, metrics.max_nesting, findings
+    )
+}
+
+/// Identifies the diagnostics of `content` under the given generation of the
+/// configuration.
+fn content_result_id(generation: u64, content: &str) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{}-{:x}", generation, hasher.finish())
+}
+
+/// Index of the innermost root containing `path`.
+fn innermost_root(roots: &[&Path], path: &Path) -> Option<usize> {
+    roots
+        .iter()
+        .enumerate()
+        .filter(|(_, root)| path.starts_with(root))
+        .max_by_key(|(_, root)| root.components().count())
+        .map(|(index, _)| index)
+}
+
+/// The `cargo-fl` section of settings sent by the client, which may or may not
+/// be wrapped in the section name.
+fn editor_settings(settings: serde_json::Value) -> serde_json::Value {
+    match settings {
+        serde_json::Value::Object(mut map) if map.contains_key("cargo-fl") => {
+            map.remove("cargo-fl").unwrap_or_default()
+        }
+        settings => settings,
+    }
+}
+
+/// Converts an issue in the document at `uri` into a diagnostic, linking the
+/// rule's documentation when `docs` configures it.
+fn to_diagnostic(uri: &Url, content: &str, issue: &Issue, docs: &DocsConfig) -> Diagnostic {
+    let related_information: Vec<DiagnosticRelatedInformation> = issue
+        .related
+        .iter()
+        .filter_map(|related| {
+            let (uri, range) = match &related.file {
+                Some(file) => {
+                    let text = std::fs::read_to_string(file).unwrap_or_default();
+                    (Url::from_file_path(file).ok()?, location_range(&text, &related.location))
+                }
+                None => (uri.clone(), location_range(content, &related.location)),
+            };
+            Some(DiagnosticRelatedInformation {
+                location: tower_lsp::lsp_types::Location { uri, range },
+                message: related.message.clone(),
+            })
+        })
+        .collect();
+    
+    Diagnostic {
+        range: location_range(content, &issue.location),
+        severity: Some(match issue.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Info => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(issue.rule.to_string())),
+        source: Some("cargo-fl".to_string()),
+        message: issue.message.clone(),
+        related_information: (!related_information.is_empty()).then_some(related_information),
+        tags: rule_tags(&issue.rule),
+        code_description: docs
+            .rule_url(&issue.rule)
+            .and_then(|url| Url::parse(&url).ok())
+            .map(|href| CodeDescription { href }),
+        data: None,
+    }
+}
+
+/// Tags that let editors render a rule's diagnostics specially, e.g. fading
+/// out unused code.
+fn rule_tags(rule: &str) -> Option<Vec<DiagnosticTag>> {
+    match rule {
+        "unused-import" => Some(vec![DiagnosticTag::UNNECESSARY]),
+        _ => None,
+    }
+}
+
+/// Converts an issue location (1-based lines, 1-based character columns)
+/// into an LSP range with UTF-16 columns.
+fn location_range(content: &str, location: &cargo_fl::rules::Location) -> Range {
+    let start_line = location.line.saturating_sub(1);
+    let end_line = location.end_line.unwrap_or(location.line).saturating_sub(1);
+    let end_column = location.end_column.unwrap_or(location.column + 1);
+    
+    Range {
+        start: Position {
+            line: start_line as u32,
+            character: utf16_column(line_text(content, start_line), location.column.saturating_sub(1)),
+        },
+        end: Position {
+            line: end_line as u32,
+            character: utf16_column(line_text(content, end_line), end_column.saturating_sub(1)),
+        },
+    }
+}
+
+fn line_text(content: &str, line: usize) -> &str {
+    content.lines().nth(line).unwrap_or("")
+}
+
+/// UTF-16 offset of the character at `char_column` in `line`.
+fn utf16_column(line: &str, char_column: usize) -> u32 {
+    line.chars().take(char_column).map(char::len_utf16).sum::<usize>() as u32
+}
+
+/// Converts a byte offset into an LSP position with a UTF-16 column.
+fn offset_to_position(content: &str, offset: usize) -> Position {
+    let offset = offset.min(content.len());
+    let prefix = &content[..offset];
+    let line_start = prefix.rfind('\n').map_or(0, |i| i + 1);
+    
+    Position {
+        line: prefix.matches('\n').count() as u32,
+        character: prefix[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+/// Converts a fix's byte-offset replacements into LSP text edits, grouped by
+/// document. Replacements aimed at other files are resolved against their
+/// contents on disk.
+fn fix_to_workspace_edit(uri: &Url, path: &Path, content: &str, fix: &Fix) -> WorkspaceEdit {
+    let mut other_files: HashMap<PathBuf, String> = HashMap::new();
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    
+    for replacement in &fix.replacements {
+        let (target_uri, text) = match replacement.file.as_deref() {
+            Some(file) if file != path => {
+                let Ok(target_uri) = Url::from_file_path(file) else { continue };
+                let text = other_files
+                    .entry(file.to_path_buf())
+                    .or_insert_with(|| std::fs::read_to_string(file).unwrap_or_default());
+                (target_uri, text.as_str())
+            }
+            _ => (uri.clone(), content),
+        };
+        
+        changes.entry(target_uri).or_default().push(TextEdit {
+            range: Range {
+                start: offset_to_position(text, replacement.start),
+                end: offset_to_position(text, replacement.end),
+            },
+            new_text: replacement.text.clone(),
+        });
+    }
+    
+    WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+        change_annotations: None,
+    }
+}
+
+/// Applies one `didChange` content change; a change without a range replaces
+/// the whole document.
+fn apply_content_change(text: &mut String, change: TextDocumentContentChangeEvent) {
+    match change.range {
+        Some(range) => {
+            let start = position_to_offset(text, range.start);
+            let end = position_to_offset(text, range.end).max(start);
+            text.replace_range(start..end, &change.text);
+        }
+        None => *text = change.text,
+    }
+}
+
+/// Converts an LSP position with a UTF-16 column into a byte offset, clamping
+/// positions past the end of a line or of the document.
+fn position_to_offset(content: &str, position: Position) -> usize {
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(position.line as usize)
+        .map(str::len)
+        .sum();
+    let line = content[line_start..].split('\n').next().unwrap_or("");
+    
+    let mut utf16 = 0;
+    for (offset, c) in line.char_indices() {
+        if utf16 >= position.character as usize {
+            return line_start + offset;
+        }
+        utf16 += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// A single edit turning `old` into `new`, covering only the changed span.
+fn minimal_text_edit(old: &str, new: &str) -> TextEdit {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
     }
+    
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    
+    TextEdit {
+        range: Range {
+            start: offset_to_position(old, prefix),
+            end: offset_to_position(old, old.len() - suffix),
+        },
+        new_text: new[prefix..new.len() - suffix].to_string(),
+    }
+}
+
+fn ranges_overlap(a: &Range, b: &Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// Whether the client asked for actions of `kind` (no filter means all kinds).
+fn kind_requested(only: Option<&Vec<CodeActionKind>>, kind: &CodeActionKind) -> bool {
+    only.is_none_or(|kinds| {
+        kinds.iter().any(|requested| {
+            kind.as_str() == requested.as_str()
+                || kind.as_str().starts_with(&format!("{}.", requested.as_str()))
+        })
+    })
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let pull_diagnostics = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|text_document| text_document.diagnostic.is_some());
+        self.pull_diagnostics.store(pull_diagnostics, Ordering::Relaxed);
+        
+        if let Some(options) = params.initialization_options {
+            *self.settings.lock().unwrap() = editor_settings(options);
+        }
+        
+        let roots: Vec<PathBuf> = match params.workspace_folders {
+            Some(folders) => folders.iter().filter_map(|folder| folder.uri.to_file_path().ok()).collect(),
+            None => params.root_uri.and_then(|uri| uri.to_file_path().ok()).into_iter().collect(),
+        };
+        let settings = self.settings();
+        *self.workspaces.lock().await = roots.into_iter().map(|root| Workspace::load(root, &settings)).collect();
+        
         Ok(InitializeResult {
             server_info: Some(ServerInfo {
                 name: "cargo-fl".to_string(),
@@ -85,109 +758,422 @@ impl LanguageServer for Backend {
                 diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
                     identifier: Some("cargo-fl".to_string()),
                     inter_file_dependencies: true,
-                    workspace_diagnostics: false,
+                    workspace_diagnostics: true,
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![
+                        CodeActionKind::QUICKFIX,
+                        CodeActionKind::SOURCE,
+                        SOURCE_FIX_ALL_CARGO_FL,
+                    ]),
                     work_done_progress_options: WorkDoneProgressOptions::default(),
+                    resolve_provider: None,
                 })),
-                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![LINT_WORKSPACE_COMMAND.to_string(), SHOW_METRICS_COMMAND.to_string()],
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
         })
     }
 
     async fn initialized(&self, _: InitializedParams) {
+        // Config files are watched so edits to them take effect immediately
+        let watchers = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/.fl.toml".to_string()),
+                    kind: None,
+                },
+                // Lets a pending workspace diagnostic pull notice edits made
+                // outside the editor
+                FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/*.rs".to_string()),
+                    kind: None,
+                },
+            ],
+        };
+        let registration = Registration {
+            id: "cargo-fl-config-watcher".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(watchers).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            self.client
+                .log_message(MessageType::WARNING, format!("Could not watch workspace files: {}", err))
+                .await;
+        }
+        
         self.client
             .log_message(MessageType::INFO, "cargo-fl LSP server initialized")
             .await;
     }
 
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let settings = self.settings();
+        {
+            let mut workspaces = self.workspaces.lock().await;
+            for folder in params.event.removed {
+                if let Ok(root) = folder.uri.to_file_path() {
+                    workspaces.retain(|workspace| workspace.root != root);
+                }
+            }
+            for folder in params.event.added {
+                if let Ok(root) = folder.uri.to_file_path() {
+                    workspaces.push(Workspace::load(root, &settings));
+                }
+            }
+        }
+        
+        self.refresh_diagnostics().await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        // Clients using the pull model send no settings with the notification
+        let settings = match params.settings {
+            serde_json::Value::Null => self.fetch_settings().await.unwrap_or_default(),
+            settings => editor_settings(settings),
+        };
+        *self.settings.lock().unwrap() = settings;
+        
+        self.reload().await;
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        self.workspace_changed.notify_waiters();
+        
+        let config_changed = params
+            .changes
+            .iter()
+            .any(|change| change.uri.path().ends_with("/.fl.toml"));
+        
+        if config_changed {
+            self.reload().await;
+        }
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<serde_json::Value>> {
+        if params.command == SHOW_METRICS_COMMAND {
+            return Ok(None);
+        }
+        if params.command != LINT_WORKSPACE_COMMAND {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(format!("Unknown command {}", params.command)));
+        }
+        
+        // Clients pulling diagnostics fetch the workspace report themselves
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            let _ = self.client.workspace_diagnostic_refresh().await;
+            return Ok(None);
+        }
+        if !self.enabled() {
+            return Ok(None);
+        }
+        
+        let token = params.work_done_progress_params.work_done_token;
+        let progress = WorkDone::begin(&self.client, token, "Linting workspace").await;
+        let report = self.workspace_report(HashMap::new(), &progress).await;
+        progress.end(format!("Linted {} files", report.items.len())).await;
+        
+        for item in report.items {
+            if let WorkspaceDocumentDiagnosticReport::Full(full) = item {
+                let diagnostics = full.full_document_diagnostic_report.items;
+                self.client.publish_diagnostics(full.uri, diagnostics, None).await;
+            }
+        }
+        
+        Ok(None)
+    }
+
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        let diagnostics = self.lint_document(&params.text_document.uri).await.unwrap_or_default();
+        let document = params.text_document;
+        self.documents.lock().unwrap().insert(document.uri.clone(), Document {
+            text: document.text,
+            version: document.version,
+        });
+        // Open documents leave the workspace report
+        self.workspace_changed.notify_waiters();
         
-        self.client
-            .publish_diagnostics(params.text_document.uri.clone(), diagnostics, None)
-            .await;
+        self.lint_and_publish(&document.uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        let diagnostics = self.lint_document(&params.text_document.uri).await.unwrap_or_default();
+        let uri = params.text_document.uri;
+        let version = params.text_document.version;
         
-        self.client
-            .publish_diagnostics(params.text_document.uri.clone(), diagnostics, None)
-            .await;
+        {
+            let mut documents = self.documents.lock().unwrap();
+            let Some(document) = documents.get_mut(&uri) else { return };
+            for change in params.content_changes {
+                apply_content_change(&mut document.text, change);
+            }
+            document.version = version;
+        }
+        
+        if self.pull_diagnostics.load(Ordering::Relaxed) {
+            return;
+        }
+        
+        // Only lint once the user pauses typing: each edit restarts the wait
+        let backend = self.clone();
+        let lint_uri = uri.clone();
+        let lint = tokio::spawn(async move {
+            tokio::time::sleep(LINT_DEBOUNCE).await;
+            backend.lint_and_publish(&lint_uri).await;
+        });
+        if let Some(superseded) = self.pending_lints.lock().unwrap().insert(uri, lint) {
+            superseded.abort();
+        }
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
-        let diagnostics = self.lint_document(&params.text_document.uri).await.unwrap_or_default();
+        self.lint_and_publish(&params.text_document.uri).await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.lock().unwrap().remove(&uri);
+        if let Some(pending) = self.pending_lints.lock().unwrap().remove(&uri) {
+            pending.abort();
+        }
+        // The file on disk is part of the workspace report again
+        self.workspace_changed.notify_waiters();
         
-        self.client
-            .publish_diagnostics(params.text_document.uri.clone(), diagnostics, None)
-            .await;
+        // Diagnostics of closed documents are no longer kept up to date
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+
+    async fn diagnostic(&self, params: DocumentDiagnosticParams) -> Result<DocumentDiagnosticReportResult> {
+        let uri = params.text_document.uri;
+        let path = uri.to_file_path().map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Not a file URI"))?;
+        let content = self.document_text(&uri, &path);
+        let result_id = self.result_id(&content);
+        
+        let unchanged = |result_id: String| {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport { result_id },
+            })
+        };
+        
+        if params.previous_result_id.as_ref() == Some(&result_id) {
+            return Ok(unchanged(result_id).into());
+        }
+        
+        let docs = self.config_for(&path).await.docs;
+        let report = match self.analyze_source(&path, &content).await {
+            Some(issues) => DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: Some(result_id),
+                    items: issues.iter().map(|issue| to_diagnostic(&uri, &content, issue, &docs)).collect(),
+                },
+            }),
+            // Keep showing the last diagnostics while the document doesn't parse
+            None => match params.previous_result_id {
+                Some(previous) => unchanged(previous),
+                None => DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport::default()),
+            },
+        };
+        
+        Ok(report.into())
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        if !self.enabled() {
+            return Ok(WorkspaceDiagnosticReport::default().into());
+        }
+        
+        let previous_result_ids: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|previous| (previous.uri, previous.value))
+            .collect();
+        let token = params.work_done_progress_params.work_done_token;
+        
+        // Clients pull again as soon as a report arrives, so a report with
+        // nothing new is held back until a document or watched file changes
+        loop {
+            let changed = self.workspace_changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+            
+            let progress = WorkDone::begin(&self.client, token.clone(), "Linting workspace").await;
+            let report = self.workspace_report(previous_result_ids.clone(), &progress).await;
+            progress.end(format!("Linted {} files", report.items.len())).await;
+            
+            let unchanged = report
+                .items
+                .iter()
+                .all(|item| matches!(item, WorkspaceDocumentDiagnosticReport::Unchanged(_)));
+            if !unchanged {
+                return Ok(report.into());
+            }
+            changed.await;
+        }
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params.position;
+        let (_, content, issues) = self
+            .analyze_document(&params.text_document_position_params.text_document.uri)
+            .await?;
+        
+        let hovered = function_metric_ranges(&content)
+            .into_iter()
+            .find(|(range, _)| range.start <= position && position <= range.end);
+        
+        Ok(hovered.map(|(range, metrics)| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: metrics_hover(&metrics, findings_in(&metrics, &issues)),
+            }),
+            range: Some(range),
+        }))
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let (_, content, issues) = self.analyze_document(&params.text_document.uri).await?;
+        
+        let lenses = function_metric_ranges(&content)
+            .into_iter()
+            .map(|(range, metrics)| CodeLens {
+                range,
+                command: Some(Command {
+                    title: metrics_lens_title(&metrics, findings_in(&metrics, &issues)),
+                    command: SHOW_METRICS_COMMAND.to_string(),
+                    arguments: None,
+                }),
+                data: None,
+            })
+            .collect();
+        
+        Ok(Some(lenses))
     }
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
         let uri = &params.text_document.uri;
-        let path = uri.to_file_path().map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?;
-        
-        let analyzer = self.analyzer.lock().await;
-        let results = analyzer.analyze_file(&path);
+        let (path, content, issues) = self.analyze_document(uri).await?;
+        let docs = self.config_for(&path).await.docs;
+        let only = params.context.only.as_ref();
         
         let mut actions = Vec::new();
         
-        if let Some(issues) = results.file_issues.get(&path) {
-            for issue in issues {
-                if issue.fix.is_some() {
-                    let range = Range {
-                        start: Position {
-                            line: issue.location.line.saturating_sub(1) as u32,
-                            character: issue.location.column.saturating_sub(1) as u32,
-                        },
-                        end: Position {
-                            line: issue.location.line.saturating_sub(1) as u32,
-                            character: issue.location.end_column.unwrap_or(issue.location.column + 1).saturating_sub(1) as u32,
-                        },
-                    };
-
-                    if params.range.start <= range.start && range.end <= params.range.end {
-                        let fix = issue.fix.as_ref().unwrap();
-                        let fix_text = &fix.description;
-                        let action = CodeAction {
-                            title: format!("Fix: {}", issue.message),
-                            kind: Some(CodeActionKind::QUICKFIX),
-                            diagnostics: Some(vec![Diagnostic {
-                                range,
-                                severity: Some(DiagnosticSeverity::WARNING),
-                                code: Some(NumberOrString::String(issue.rule.to_string())),
-                                source: Some("cargo-fl".to_string()),
-                                message: issue.message.clone(),
-                                related_information: None,
-                                tags: None,
-                                code_description: None,
-                                data: None,
-                            }]),
-                            edit: Some(WorkspaceEdit {
-                                changes: {
-                                    let mut changes = HashMap::new();
-                                    changes.insert(uri.clone(), vec![TextEdit {
-                                        range,
-                                        new_text: fix_text.clone(),
-                                    }]);
-                                    Some(changes)
-                                },
-                                document_changes: None,
-                                change_annotations: None,
-                            }),
-                            command: None,
-                            is_preferred: Some(true),
-                            disabled: None,
-                            data: None,
-                        };
-                        actions.push(CodeActionOrCommand::CodeAction(action));
-                    }
+        if kind_requested(only, &CodeActionKind::QUICKFIX) {
+            let mut suppressed = HashSet::new();
+            
+            for issue in &issues {
+                let diagnostic = to_diagnostic(uri, &content, issue, &docs);
+                if !ranges_overlap(&diagnostic.range, &params.range) {
+                    continue;
+                }
+                
+                if let Some(fix) = issue.fix.as_ref().filter(|fix| !fix.replacements.is_empty()) {
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: format!("Fix: {}", fix.description),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diagnostic.clone()]),
+                        edit: Some(fix_to_workspace_edit(uri, &path, &content, fix)),
+                        command: None,
+                        is_preferred: Some(fix.applicability == Applicability::Safe),
+                        disabled: None,
+                        data: None,
+                    }));
                 }
+                
+                // One suppression per rule and line
+                if suppressed.insert((issue.rule.clone(), issue.location.line)) {
+                    let line = issue.location.line.saturating_sub(1);
+                    let indent: String = line_text(&content, line)
+                        .chars()
+                        .take_while(|c| c.is_whitespace())
+                        .collect();
+                    let position = Position { line: line as u32, character: 0 };
+                    
+                    let mut changes = HashMap::new();
+                    changes.insert(uri.clone(), vec![TextEdit {
+                        range: Range { start: position, end: position },
+                        new_text: format!("{}{}\n", indent, suppression_comment(&issue.rule)),
+                    }]);
+                    
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: format!("Suppress {} for this line", issue.rule),
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diagnostic]),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(changes),
+                            document_changes: None,
+                            change_annotations: None,
+                        }),
+                        command: None,
+                        is_preferred: Some(false),
+                        disabled: None,
+                        data: None,
+                    }));
+                }
+            }
+        }
+        
+        if kind_requested(only, &CodeActionKind::SOURCE) {
+            let mut rules: Vec<&str> = issues
+                .iter()
+                .filter(|issue| issue.fix.is_some())
+                .map(|issue| issue.rule.as_str())
+                .collect();
+            rules.sort();
+            rules.dedup();
+            
+            for rule in rules {
+                let edit = self
+                    .fix_all_edit(uri, &path, &content, &issues, false, |issue| issue.rule == rule)
+                    .await;
+                if let Some(edit) = edit {
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: format!("Fix all {} in file", rule),
+                        kind: Some(CodeActionKind::SOURCE),
+                        diagnostics: None,
+                        edit: Some(edit),
+                        command: None,
+                        is_preferred: None,
+                        disabled: None,
+                        data: None,
+                    }));
+                }
+            }
+        }
+        
+        if kind_requested(only, &SOURCE_FIX_ALL_CARGO_FL) {
+            // Only fixes that preserve behaviour are applied without review
+            if let Some(edit) = self.fix_all_edit(uri, &path, &content, &issues, true, |_| true).await {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: "Fix all cargo-fl issues".to_string(),
+                    kind: Some(SOURCE_FIX_ALL_CARGO_FL),
+                    diagnostics: None,
+                    edit: Some(edit),
+                    command: None,
+                    is_preferred: None,
+                    disabled: None,
+                    data: None,
+                }));
             }
         }
         
@@ -200,6 +1186,316 @@ async fn main() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(|client| Backend::new(client));
+    let (service, socket) = LspService::new(Backend::new);
     Server::new(stdin, stdout, socket).serve(service).await;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_fl::rules::Replacement;
+    
+    #[test]
+    fn test_positions_use_utf16_columns() {
+        let content = "let s = \"héllo 🦀\";\nlet t = s;\n";
+        let crab = content.find('🦀').unwrap();
+        
+        assert_eq!(offset_to_position(content, crab), Position { line: 0, character: 15 });
+        assert_eq!(offset_to_position(content, crab + '🦀'.len_utf8()), Position { line: 0, character: 17 });
+        assert_eq!(offset_to_position(content, content.find("t =").unwrap()), Position { line: 1, character: 4 });
+        assert_eq!(utf16_column("\"héllo 🦀\" x", 10), 11);
+    }
+    
+    #[test]
+    fn test_fix_edits_replace_the_fixed_span() {
+        let content = "fn main() {\n    let v = vec![1];\n    v.get(0);\n}\n";
+        let path = PathBuf::from("/tmp/main.rs");
+        let uri = Url::from_file_path(&path).unwrap();
+        let start = content.find("get(0)").unwrap();
+        let fix = Fix {
+            description: "Replace with .first()".to_string(),
+            replacements: vec![Replacement {
+                start,
+                end: start + "get(0)".len(),
+                text: "first()".to_string(),
+                file: None,
+            }],
+            applicability: Applicability::Safe,
+        };
+        
+        let edit = fix_to_workspace_edit(&uri, &path, content, &fix);
+        let edits = &edit.changes.unwrap()[&uri];
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "first()");
+        assert_eq!(edits[0].range.start, Position { line: 2, character: 6 });
+        assert_eq!(edits[0].range.end, Position { line: 2, character: 12 });
+        
+        let minimal = minimal_text_edit(content, &content.replace("get(0)", "first()"));
+        assert_eq!(minimal.range.start, Position { line: 2, character: 6 });
+        assert_eq!(minimal.range.end, Position { line: 2, character: 11 });
+        assert_eq!(minimal.new_text, "first(");
+    }
+    
+    #[test]
+    fn test_incremental_content_changes() {
+        let mut text = "fn main() {\n    let s = \"🦀\";\n}\n".to_string();
+        let change = |start: (u32, u32), end: (u32, u32), new_text: &str| TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position { line: start.0, character: start.1 },
+                end: Position { line: end.0, character: end.1 },
+            }),
+            range_length: None,
+            text: new_text.to_string(),
+        };
+        
+        // The crab is two UTF-16 code units wide
+        apply_content_change(&mut text, change((1, 15), (1, 15), "!"));
+        assert_eq!(text, "fn main() {\n    let s = \"🦀!\";\n}\n");
+        
+        apply_content_change(&mut text, change((1, 4), (2, 0), ""));
+        assert_eq!(text, "fn main() {\n    }\n");
+        
+        apply_content_change(&mut text, change((2, 0), (2, 0), "// end\n"));
+        assert_eq!(text, "fn main() {\n    }\n// end\n");
+        
+        apply_content_change(&mut text, TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "fn f() {}".to_string(),
+        });
+        assert_eq!(text, "fn f() {}");
+    }
+    
+    #[test]
+    fn test_innermost_root() {
+        let roots = [Path::new("/work/app"), Path::new("/work"), Path::new("/work/app/crates/core")];
+        
+        assert_eq!(innermost_root(&roots, Path::new("/work/app/src/main.rs")), Some(0));
+        assert_eq!(innermost_root(&roots, Path::new("/work/app/crates/core/lib.rs")), Some(2));
+        assert_eq!(innermost_root(&roots, Path::new("/work/other/lib.rs")), Some(1));
+        // Prefixes only match whole components
+        assert_eq!(innermost_root(&roots, Path::new("/workshop/lib.rs")), None);
+    }
+    
+    #[test]
+    fn test_editor_settings_section() {
+        let wrapped = serde_json::json!({ "cargo-fl": { "enable": false } });
+        assert_eq!(editor_settings(wrapped), serde_json::json!({ "enable": false }));
+        
+        let bare = serde_json::json!({ "rules": { "check_naming": false } });
+        assert_eq!(editor_settings(bare.clone()), bare);
+    }
+    
+    #[tokio::test]
+    async fn test_pull_diagnostics_result_ids() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("main.rs");
+        std::fs::write(&path, "fn main() {\n    x.unwrap();\n}\n").unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+        
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        let pull = |previous_result_id: Option<String>| DocumentDiagnosticParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            identifier: None,
+            previous_result_id,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        
+        let Ok(DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(full))) =
+            backend.diagnostic(pull(None)).await
+        else {
+            panic!("expected a full report");
+        };
+        let report = full.full_document_diagnostic_report;
+        assert!(report.items.iter().any(|d| d.code == Some(NumberOrString::String("unwrap_usage".into()))));
+        
+        let again = backend.diagnostic(pull(report.result_id.clone())).await.unwrap();
+        assert!(matches!(again, DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(_))));
+        
+        std::fs::write(&path, "fn main() {}\n").unwrap();
+        let changed = backend.diagnostic(pull(report.result_id)).await.unwrap();
+        assert!(matches!(changed, DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_changes_are_linted_in_the_background() {
+        let uri = Url::parse("file:///tmp/debounce/main.rs").unwrap();
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        backend
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(uri.clone(), "rust".to_string(), 1, "fn main() {}\n".to_string()),
+            })
+            .await;
+        
+        let started = std::time::Instant::now();
+        for version in 2..4 {
+            backend
+                .did_change(DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version },
+                    content_changes: vec![TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text: format!("fn main() {{}} // {}\n", version),
+                    }],
+                })
+                .await;
+        }
+        assert!(started.elapsed() < LINT_DEBOUNCE);
+        assert_eq!(backend.pending_lints.lock().unwrap().len(), 1);
+        
+        backend
+            .did_close(DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+            })
+            .await;
+        assert!(backend.pending_lints.lock().unwrap().is_empty());
+        assert!(backend.documents.lock().unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_workspace_diagnostics_wait_for_changes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let path = root.join("main.rs");
+        std::fs::write(&path, "fn main() {\n    x.unwrap();\n}\n").unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+        
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        #[allow(deprecated)]
+        let params = InitializeParams {
+            root_uri: Some(Url::from_file_path(&root).unwrap()),
+            ..Default::default()
+        };
+        backend.initialize(params).await.unwrap();
+        let pull = |previous_result_ids: Vec<PreviousResultId>| WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        
+        let Ok(WorkspaceDiagnosticReportResult::Report(report)) = backend.workspace_diagnostic(pull(Vec::new())).await
+        else {
+            panic!("expected a report");
+        };
+        let [WorkspaceDocumentDiagnosticReport::Full(full)] = &report.items[..] else {
+            panic!("expected a full report for main.rs");
+        };
+        let previous = vec![PreviousResultId {
+            uri: uri.clone(),
+            value: full.full_document_diagnostic_report.result_id.clone().unwrap(),
+        }];
+        
+        // Nothing changed, so the pull stays pending
+        let pending = backend.workspace_diagnostic(pull(previous));
+        tokio::pin!(pending);
+        assert!(tokio::time::timeout(Duration::from_millis(100), pending.as_mut()).await.is_err());
+        
+        std::fs::write(&path, "fn main() {}\n").unwrap();
+        backend
+            .did_change_watched_files(DidChangeWatchedFilesParams {
+                changes: vec![FileEvent { uri: uri.clone(), typ: FileChangeType::CHANGED }],
+            })
+            .await;
+        let Ok(WorkspaceDiagnosticReportResult::Report(report)) =
+            tokio::time::timeout(Duration::from_secs(5), pending).await.unwrap()
+        else {
+            panic!("expected a report");
+        };
+        let [WorkspaceDocumentDiagnosticReport::Full(full)] = &report.items[..] else {
+            panic!("expected a full report for main.rs");
+        };
+        assert!(full.full_document_diagnostic_report.items.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_complexity_hover_and_lens() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("lib.rs");
+        std::fs::write(&path, "fn /* é */ run(x: u8, y: Option<u8>) {\n    if x > 1 {\n        y.unwrap();\n    }\n}\n").unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+        
+        let (service, _socket) = LspService::new(Backend::new);
+        let backend = service.inner();
+        
+        let lenses = backend
+            .code_lens(CodeLensParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lenses.len(), 1);
+        assert_eq!(lenses[0].range.start, Position { line: 0, character: 11 });
+        let command = lenses[0].command.clone().unwrap();
+        assert_eq!(command.title, "cyclomatic 2 · cognitive 1 · nesting 1 · 1 finding");
+        let clicked = backend
+            .execute_command(ExecuteCommandParams {
+                command: command.command,
+                arguments: Vec::new(),
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            })
+            .await;
+        assert_eq!(clicked, Ok(None));
+        
+        let hover_at = |character| HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position { line: 0, character },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let hover = backend.hover(hover_at(12)).await.unwrap().unwrap();
+        let HoverContents::Markup(markup) = hover.contents else { panic!("expected markdown") };
+        assert!(markup.value.contains("| Max nesting depth | 1 |"));
+        assert!(backend.hover(hover_at(2)).await.unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_diagnostic_tags_related_info_and_docs() {
+        let content = "use serde::Serialize;\nuse std::fmt;\n";
+        let uri = Url::parse("file:///tmp/lib.rs").unwrap();
+        let location = |line| cargo_fl::rules::Location { line, column: 1, end_line: None, end_column: None };
+        let issue = |rule: &str, related| Issue {
+            rule: rule.to_string(),
+            severity: Severity::Info,
+            message: String::new(),
+            location: location(1),
+            fix: None,
+            related,
+        };
+        let docs: DocsConfig = toml::from_str("url_template = \"https://lints.example/{rule}\"").unwrap();
+        
+        let unused = to_diagnostic(&uri, content, &issue("unused-import", Vec::new()), &docs);
+        assert_eq!(unused.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+        assert_eq!(unused.related_information, None);
+        assert_eq!(unused.code_description.unwrap().href.as_str(), "https://lints.example/unused-import");
+        
+        let related = vec![cargo_fl::rules::RelatedLocation {
+            location: location(2),
+            message: "Move the import below `use std::fmt`".to_string(),
+            file: None,
+        }];
+        let misplaced = to_diagnostic(&uri, content, &issue("import-order", related), &DocsConfig::default());
+        assert_eq!(misplaced.tags, None);
+        assert_eq!(misplaced.code_description, None);
+        let related = &misplaced.related_information.unwrap()[0];
+        assert_eq!(related.location.uri, uri);
+        assert_eq!(related.location.range.start, Position { line: 1, character: 0 });
+    }
+    
+    #[test]
+    fn test_requested_kinds() {
+        let only = vec![CodeActionKind::SOURCE_FIX_ALL];
+        assert!(kind_requested(Some(&only), &SOURCE_FIX_ALL_CARGO_FL));
+        assert!(!kind_requested(Some(&only), &CodeActionKind::QUICKFIX));
+        assert!(!kind_requested(Some(&only), &CodeActionKind::SOURCE));
+        assert!(kind_requested(None, &CodeActionKind::QUICKFIX));
+    }
+}
@@ -1,21 +1,20 @@
-use clap::{Parser, Subcommand};
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-mod analyzer;
-mod config;
-mod rules;
-mod walker;
-mod cache;
-mod incremental;
-mod ast_cache;
-mod autofix;
-
-use analyzer::Analyzer;
-use config::{Config, ConfigManager};
+use ahash::AHashMap;
+use cargo_fl::analyzer::{AnalysisResults, AnalysisStats, Analyzer};
+use cargo_fl::config::{Config, ConfigManager};
+use cargo_fl::git::{self, GitScope, GitSelection};
+use cargo_fl::interactive::InteractiveReviewer;
+use cargo_fl::metrics::{Level, MetricsReport, SortKey};
+use cargo_fl::todos::TodoReport;
+use cargo_fl::walker::RustFileWalker;
+use cargo_fl::watch::Watcher;
 
 #[derive(Parser)]
 #[command(name = "cargo-fl")]
@@ -29,22 +28,37 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Run linter on project
-    Check {
-        /// Path to check (default: current directory)
+    Check(CheckArgs),
+    
+    /// Lint once, then re-lint changed files as they are saved
+    Watch {
+        /// Path to watch (default: current directory)
         #[arg(default_value = ".")]
         path: PathBuf,
         
-        /// Fix auto-fixable issues
-        #[arg(long, short)]
-        fix: bool,
+        /// How long to gather changes before re-linting, in milliseconds. Also
+        /// how often to check for them where the filesystem can't report them
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+    },
+    
+    /// Report size and complexity per function, file and module
+    Metrics(MetricsArgs),
+    
+    /// List TODO, FIXME, XXX and HACK comments by tag, owner and file
+    Todos {
+        /// Files and directories to search (default: current directory)
+        paths: Vec<PathBuf>,
         
-        /// Output format (default, json, github)
-        #[arg(long, default_value = "default")]
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
         format: String,
-        
-        /// Exit with code 1 if any issues found
-        #[arg(long)]
-        strict: bool,
+    },
+    
+    /// Manage the git pre-commit hook
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
     },
     
     /// Show/modify configuration
@@ -59,6 +73,184 @@ enum Commands {
     },
 }
 
+#[derive(Args)]
+struct CheckArgs {
+    /// Files and directories to check (default: current directory)
+    paths: Vec<PathBuf>,
+    
+    /// Read the paths to check from FILE, one per line (`-` for stdin)
+    #[arg(long, value_name = "FILE", conflicts_with = "stdin")]
+    files_from: Option<PathBuf>,
+    
+    /// Check source read from stdin instead of files
+    #[arg(long, conflicts_with_all = ["paths", "interactive", "git_scope"])]
+    stdin: bool,
+    
+    /// Path the stdin source is checked as, for config and ignore files
+    /// (default: stdin.rs; requires --stdin)
+    #[arg(long, value_name = "PATH")]
+    stdin_filename: Option<PathBuf>,
+    
+    /// Skip files matching GLOB, on top of `ignore` in .fl.toml
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+    
+    /// Only check files matching GLOB, on top of `include` in .fl.toml
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+    
+    /// Don't skip files because of ignore files or `ignore` in .fl.toml
+    #[arg(long)]
+    no_ignore: bool,
+    
+    /// Fix auto-fixable issues (with --stdin, print the fixed source)
+    #[arg(long, short)]
+    fix: bool,
+    
+    /// Review each fix before applying it (requires --fix)
+    #[arg(long, short, requires = "fix")]
+    interactive: bool,
+    
+    /// Output format (default, json, github)
+    #[arg(long, default_value = "default")]
+    format: String,
+    
+    /// Exit with code 1 if any issues found
+    #[arg(long)]
+    strict: bool,
+    
+    /// Only lint files with uncommitted changes, plus untracked files
+    #[arg(long, group = "git_scope")]
+    changed: bool,
+    
+    /// Only lint files changed since branching off REF, plus untracked files
+    #[arg(long, value_name = "REF", group = "git_scope")]
+    since: Option<String>,
+    
+    /// Only lint staged files
+    #[arg(long, group = "git_scope")]
+    staged: bool,
+    
+    /// Only lint untracked files
+    #[arg(long, group = "git_scope")]
+    untracked: bool,
+    
+    /// Only report issues on lines the selected changes touch
+    #[arg(long, requires = "git_scope")]
+    diff_lines_only: bool,
+}
+
+#[derive(Args)]
+struct MetricsArgs {
+    /// Files and directories to measure (default: current directory)
+    paths: Vec<PathBuf>,
+    
+    /// Output format (text, csv, json)
+    #[arg(long, default_value = "text")]
+    format: String,
+    
+    /// Order rows by name, loc, sloc, cyclomatic, cognitive, nesting, params, unsafe or unwraps
+    #[arg(long, value_name = "KEY", default_value = "name")]
+    sort: SortKey,
+    
+    /// Only output the function, file or module table (repeatable; default: all)
+    #[arg(long)]
+    level: Vec<Level>,
+    
+    /// Only output the first N rows of each table
+    #[arg(long, value_name = "N")]
+    top: Option<usize>,
+}
+
+impl CheckArgs {
+    /// The config for `path` with the command-line globs applied.
+    fn config(&self, path: &Path) -> Config {
+        let mut config = Config::load_for(path);
+        if self.no_ignore {
+            config.ignore.clear();
+        }
+        config.ignore.extend(self.exclude.iter().cloned());
+        config.include.extend(self.include.iter().cloned());
+        config
+    }
+    
+    fn analyzer(&self, config: Config) -> Analyzer {
+        let analyzer = Analyzer::new(config);
+        if self.no_ignore {
+            analyzer.without_ignore_files()
+        } else {
+            analyzer
+        }
+    }
+    
+    /// An analyzer for each group of `paths` governed by the same config,
+    /// with the git state of their repositories when `git_scope` is set.
+    fn analyzers(&self, paths: &[PathBuf], git_scope: Option<&GitScope>) -> Vec<(Analyzer, Vec<PathBuf>)> {
+        let mut analyzers = Vec::new();
+        for group in group_by_config(paths) {
+            let mut analyzer = self.analyzer(self.config(&group[0]));
+            if let Some(scope) = git_scope {
+                match GitSelection::load_all(&group, scope, self.diff_lines_only) {
+                    Ok(selection) => analyzer = analyzer.with_git_selection(selection),
+                    Err(e) => {
+                        eprintln!("{} {}", "✗".red().bold(), e);
+                        process::exit(2);
+                    }
+                }
+            }
+            analyzers.push((analyzer, group));
+        }
+        analyzers
+    }
+    
+    fn git_scope(&self) -> Option<GitScope> {
+        if self.changed {
+            Some(GitScope::Changed)
+        } else if self.staged {
+            Some(GitScope::Staged)
+        } else if self.untracked {
+            Some(GitScope::Untracked)
+        } else {
+            self.since.clone().map(GitScope::Since)
+        }
+    }
+    
+    /// The paths given on the command line and through `--files-from`.
+    fn paths(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut paths = self.paths.clone();
+        
+        if let Some(list) = &self.files_from {
+            let content = if list.as_os_str() == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                std::fs::read_to_string(list)?
+            };
+            paths.extend(content.lines().map(str::trim).filter(|line| !line.is_empty()).map(PathBuf::from));
+        } else if paths.is_empty() {
+            paths.push(PathBuf::from("."));
+        }
+        
+        Ok(paths)
+    }
+}
+
+#[derive(Subcommand)]
+enum HookAction {
+    /// Write a pre-commit hook that runs `cargo-fl check --staged --strict`
+    Install {
+        /// Fix staged files and re-stage them before committing
+        #[arg(long)]
+        fix: bool,
+        
+        /// Replace an existing pre-commit hook not written by cargo-fl
+        #[arg(long)]
+        force: bool,
+    },
+    
+    /// Remove the pre-commit hook written by `hook install`
+    Uninstall,
+}
+
 fn main() {
     let cli = Cli::parse();
     
@@ -75,8 +267,29 @@ fn main() {
 
 fn handle_command(cli: Cli) {
     match cli.command {
-        Commands::Check { path, fix, format, strict } => {
-            run_check(path, fix, format, strict);
+        // Checked here because clap treats the `--stdin` flag as always present
+        Commands::Check(args) if args.stdin_filename.is_some() && !args.stdin => {
+            Cli::command()
+                .error(ErrorKind::MissingRequiredArgument, "--stdin-filename requires --stdin")
+                .exit();
+        }
+        Commands::Check(args) if args.stdin => {
+            run_check_stdin(&args);
+        }
+        Commands::Check(args) => {
+            run_check(&args);
+        }
+        Commands::Watch { path, interval } => {
+            run_watch(path, interval);
+        }
+        Commands::Metrics(args) => {
+            run_metrics(args);
+        }
+        Commands::Todos { paths, format } => {
+            run_todos(paths, &format);
+        }
+        Commands::Hook { action } => {
+            handle_hook(action);
         }
         Commands::Config { show, init } => {
             handle_config(show, init);
@@ -84,14 +297,25 @@ fn handle_command(cli: Cli) {
     }
 }
 
-fn run_check(path: PathBuf, fix: bool, format: String, strict: bool) {
+fn run_check(args: &CheckArgs) {
     let start = Instant::now();
     
-    // Load config
-    let config = Config::load_or_default(&path);
+    let paths = match args.paths() {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("{} Failed to read the file list: {}", "✗".red().bold(), e);
+            process::exit(2);
+        }
+    };
+    if paths.is_empty() {
+        println!("{} 0 files", "✓ Checked".green().bold());
+        return;
+    }
+    
+    let git_scope = args.git_scope();
+    let restage = args.fix && git_scope == Some(GitScope::Staged);
     
-    // Create analyzer
-    let mut analyzer = Analyzer::new(config);
+    let analyzers = args.analyzers(&paths, git_scope.as_ref());
     
     // Walk files and analyze
     let pb = ProgressBar::new_spinner();
@@ -102,19 +326,167 @@ fn run_check(path: PathBuf, fix: bool, format: String, strict: bool) {
     );
     pb.set_message("Analyzing files...");
     
-    let results = if fix {
-        analyzer.analyze_path_with_autofix(&path)
+    let mut reviewer = args.interactive.then(|| {
+        pb.finish_and_clear();
+        InteractiveReviewer::stdio()
+    });
+    let mut results: Option<AnalysisResults> = None;
+    for (mut analyzer, paths) in analyzers {
+        let group_results = if let Some(reviewer) = reviewer.as_mut() {
+            analyzer.analyze_paths_with_review(&paths, &mut |file, issue| reviewer.review(file, issue))
+        } else if args.fix {
+            analyzer.analyze_paths_with_autofix(&paths)
+        } else {
+            analyzer.analyze_paths(&paths)
+        };
+        match results.as_mut() {
+            Some(results) => results.merge(group_results),
+            None => results = Some(group_results),
+        }
+    }
+    pb.finish_and_clear();
+    if let Some(reviewer) = reviewer {
+        println!(
+            "\n{} {} fixes accepted, {} skipped",
+            "→".yellow(),
+            reviewer.accepted,
+            reviewer.skipped
+        );
+    }
+    let Some(mut results) = results else { return };
+    
+    let mut written = Vec::new();
+    for (file, content) in results.fixed_files.iter().flatten() {
+        if let Err(e) = std::fs::write(file, content) {
+            eprintln!("{} Failed to write {}: {}", "✗".red().bold(), file.display(), e);
+        } else {
+            written.push(file.clone());
+        }
+    }
+    
+    // Fixes to staged files belong in the commit being made
+    if restage {
+        if let Err(e) = git::stage(&written) {
+            eprintln!("{} Failed to re-stage fixed files: {}", "✗".red().bold(), e);
+        }
+        for file in &results.partially_staged {
+            eprintln!(
+                "{} Not fixing {}: it has unstaged changes",
+                "→".yellow(),
+                file.display()
+            );
+        }
+    }
+    
+    // Only what is left after fixing is reported, and decides --strict
+    if !written.is_empty() {
+        let remaining = args
+            .analyzers(&paths, git_scope.as_ref())
+            .into_iter()
+            .map(|(mut analyzer, paths)| analyzer.analyze_paths(&paths))
+            .reduce(|mut remaining, other| {
+                remaining.merge(other);
+                remaining
+            });
+        if let Some(remaining) = remaining {
+            println!("{} Fixed {} files", "✓".green().bold(), written.len());
+            results = AnalysisResults {
+                fixed_files: results.fixed_files.take(),
+                ..remaining
+            };
+        }
+    }
+    
+    print_results(&results, &args.format, args.fix, start.elapsed());
+    
+    if args.strict && results.total_issues() > 0 {
+        process::exit(1);
+    }
+}
+
+/// Lints source from stdin as if it were `--stdin-filename`.
+fn run_check_stdin(args: &CheckArgs) {
+    let start = Instant::now();
+    let path = args.stdin_filename.as_deref().unwrap_or(Path::new("stdin.rs"));
+    
+    let source = match std::io::read_to_string(std::io::stdin()) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("{} Failed to read stdin: {}", "✗".red().bold(), e);
+            process::exit(2);
+        }
+    };
+    
+    // Ignored files pass through untouched, as they would be skipped on disk
+    let mut analyzer = args.analyzer(args.config(path));
+    if analyzer.is_ignored(path) {
+        if args.fix {
+            print!("{}", source);
+        }
+        return;
+    }
+    
+    let Some(issues) = analyzer.analyze_source(path, &source) else {
+        eprintln!("{} Failed to parse {}", "✗".red().bold(), path.display());
+        if args.fix {
+            print!("{}", source);
+        }
+        process::exit(2);
+    };
+    
+    let issues = if args.fix {
+        let fixed = analyzer.fix_source(path, &source, &issues);
+        print!("{}", fixed);
+        
+        // stdout carries the fixed source, so report what is left on stderr
+        let remaining = analyzer.analyze_source(path, &fixed).unwrap_or(issues);
+        if !remaining.is_empty() {
+            eprintln!("{}", path.display().to_string().bold());
+            for issue in &remaining {
+                eprintln!("{}", issue.display());
+            }
+        }
+        remaining
     } else {
-        analyzer.analyze_path(&path)
+        let results = AnalysisResults {
+            stats: AnalysisStats {
+                total_files: 1,
+                files_with_issues: usize::from(!issues.is_empty()),
+                total_issues: issues.len(),
+                ..Default::default()
+            },
+            file_issues: AHashMap::from_iter([(path.to_path_buf(), issues.clone())]),
+            performance_stats: None,
+            fixed_files: None,
+            partially_staged: Vec::new(),
+        };
+        print_results(&results, &args.format, false, start.elapsed());
+        issues
     };
-    pb.finish_and_clear();
     
-    // Display results
+    if args.strict && !issues.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// `paths` grouped by the `.fl.toml` governing them, in their original order.
+fn group_by_config(paths: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let mut groups: Vec<(Option<PathBuf>, Vec<PathBuf>)> = Vec::new();
+    for path in paths {
+        let config_dir = Config::config_dir_for(path);
+        match groups.iter_mut().find(|(dir, _)| *dir == config_dir) {
+            Some((_, group)) => group.push(path.clone()),
+            None => groups.push((config_dir, vec![path.clone()])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).collect()
+}
+
+fn print_results(results: &AnalysisResults, format: &str, fix: bool, duration: Duration) {
     let issue_count = results.total_issues();
     let file_count = results.file_count();
-    let duration = start.elapsed();
     
-    match format.as_str() {
+    match format {
         "json" => {
             println!("{}", serde_json::to_string_pretty(&results).unwrap());
         }
@@ -169,8 +541,89 @@ fn run_check(path: PathBuf, fix: bool, format: String, strict: bool) {
             }
         }
     }
+}
+
+fn run_watch(path: PathBuf, interval: u64) {
+    let mut watcher = Watcher::for_path(path);
+    
+    if let Err(e) = watcher.run(Duration::from_millis(interval), &mut std::io::stdout()) {
+        eprintln!("{} {}", "✗".red().bold(), e);
+        process::exit(1);
+    }
+}
+
+fn run_metrics(args: MetricsArgs) {
+    let paths = if args.paths.is_empty() { vec![PathBuf::from(".")] } else { args.paths };
+    let levels = if args.level.is_empty() {
+        vec![Level::Function, Level::File, Level::Module]
+    } else {
+        args.level
+    };
+    
+    // Each path is walked with the ignore and include globs of its own config
+    let mut report = MetricsReport::default();
+    for group in group_by_config(&paths) {
+        let config = Config::load_for(&group[0]);
+        report.merge(MetricsReport::collect(&group, &RustFileWalker::with_config(&config)));
+    }
+    report.sort_by(args.sort);
+    if let Some(top) = args.top {
+        report.truncate(top);
+    }
+    
+    let mut out = std::io::stdout().lock();
+    let result = match args.format.as_str() {
+        "csv" => report.write_csv(&levels, &mut out),
+        "json" => serde_json::to_writer_pretty(&mut out, &report.to_json(&levels))
+            .map_err(std::io::Error::from)
+            .and_then(|()| writeln!(out)),
+        _ => report.write_text(&levels, &mut out),
+    };
+    
+    if let Err(e) = result {
+        eprintln!("{} Failed to write metrics: {}", "✗".red().bold(), e);
+        process::exit(2);
+    }
+}
+
+fn run_todos(paths: Vec<PathBuf>, format: &str) {
+    let paths = if paths.is_empty() { vec![PathBuf::from(".")] } else { paths };
+    // Each path is walked with the ignore globs and tags of its own config
+    let mut report = TodoReport::default();
+    for group in group_by_config(&paths) {
+        let config = Config::load_for(&group[0]);
+        report.merge(TodoReport::collect(&group, &RustFileWalker::with_config(&config), &config.todo_comments.tags));
+    }
+    
+    let mut out = std::io::stdout().lock();
+    let result = match format {
+        "json" => serde_json::to_writer_pretty(&mut out, &report.to_json())
+            .map_err(std::io::Error::from)
+            .and_then(|()| writeln!(out)),
+        _ => report.write_text(&mut out),
+    };
+    
+    if let Err(e) = result {
+        eprintln!("{} Failed to write comments: {}", "✗".red().bold(), e);
+        process::exit(2);
+    }
+}
+
+fn handle_hook(action: HookAction) {
+    let dir = PathBuf::from(".");
+    
+    let result = match action {
+        HookAction::Install { fix, force } => git::install_hook(&dir, fix, force).map(|hook| {
+            println!("{} Installed {}", "✓".green().bold(), hook.display());
+        }),
+        HookAction::Uninstall => git::uninstall_hook(&dir).map(|hook| match hook {
+            Some(hook) => println!("{} Removed {}", "✓".green().bold(), hook.display()),
+            None => println!("No cargo-fl pre-commit hook installed"),
+        }),
+    };
     
-    if strict && issue_count > 0 {
+    if let Err(e) = result {
+        eprintln!("{} {}", "✗".red().bold(), e);
         process::exit(1);
     }
 }
@@ -0,0 +1,698 @@
+use crate::ast_cache::read_rust_file;
+use crate::rules::{
+    calculate_cognitive_complexity, calculate_cyclomatic_complexity, count_sloc, file_items, max_nesting_depth,
+    CommentIndex,
+};
+use crate::walker::{crate_module, RustFileWalker};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// Size and complexity figures shared by every level of the report. For
+/// files and modules the complexities are totals over their functions, and
+/// nesting and parameters the largest among them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Metrics {
+    /// Lines, including blank and comment lines
+    pub loc: usize,
+    /// Lines with code on them
+    pub sloc: usize,
+    pub cyclomatic: usize,
+    pub cognitive: usize,
+    pub max_nesting: usize,
+    pub params: usize,
+    pub unsafe_blocks: usize,
+    /// `.unwrap()` and `.expect()` calls
+    pub unwraps: usize,
+}
+
+impl Metrics {
+    /// Adds a function's complexity to a file or module total.
+    fn add_function(&mut self, function: &Metrics) {
+        self.cyclomatic += function.cyclomatic;
+        self.cognitive += function.cognitive;
+        self.max_nesting = self.max_nesting.max(function.max_nesting);
+        self.params = self.params.max(function.params);
+    }
+
+    /// Adds a file to a module total.
+    fn add_file(&mut self, file: &Metrics) {
+        self.loc += file.loc;
+        self.sloc += file.sloc;
+        self.add_function(file);
+        self.unsafe_blocks += file.unsafe_blocks;
+        self.unwraps += file.unwraps;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionRow {
+    /// The function name, qualified with the type or trait for methods
+    pub name: String,
+    pub file: PathBuf,
+    /// 1-based line of the `fn` keyword
+    pub line: usize,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRow {
+    pub file: PathBuf,
+    /// Module path of the file, such as `rules::style`
+    pub module: String,
+    pub functions: usize,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+/// A module together with all of its submodules.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleRow {
+    pub module: String,
+    pub files: usize,
+    pub functions: usize,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+/// Which tables of a report to output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Function,
+    File,
+    Module,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "function" => Ok(Level::Function),
+            "file" => Ok(Level::File),
+            "module" => Ok(Level::Module),
+            _ => Err(format!("unknown level '{}' (expected function, file or module)", s)),
+        }
+    }
+}
+
+/// The column rows are ordered by. Names sort ascending, numbers largest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Name,
+    Loc,
+    Sloc,
+    Cyclomatic,
+    Cognitive,
+    Nesting,
+    Params,
+    Unsafe,
+    Unwraps,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(SortKey::Name),
+            "loc" => Ok(SortKey::Loc),
+            "sloc" => Ok(SortKey::Sloc),
+            "cyclomatic" => Ok(SortKey::Cyclomatic),
+            "cognitive" => Ok(SortKey::Cognitive),
+            "nesting" => Ok(SortKey::Nesting),
+            "params" => Ok(SortKey::Params),
+            "unsafe" => Ok(SortKey::Unsafe),
+            "unwraps" => Ok(SortKey::Unwraps),
+            _ => Err(format!(
+                "unknown sort key '{}' (expected name, loc, sloc, cyclomatic, cognitive, nesting, params, unsafe or unwraps)",
+                s
+            )),
+        }
+    }
+}
+
+impl SortKey {
+    fn value(self, metrics: &Metrics) -> usize {
+        match self {
+            SortKey::Name => 0,
+            SortKey::Loc => metrics.loc,
+            SortKey::Sloc => metrics.sloc,
+            SortKey::Cyclomatic => metrics.cyclomatic,
+            SortKey::Cognitive => metrics.cognitive,
+            SortKey::Nesting => metrics.max_nesting,
+            SortKey::Params => metrics.params,
+            SortKey::Unsafe => metrics.unsafe_blocks,
+            SortKey::Unwraps => metrics.unwraps,
+        }
+    }
+}
+
+/// Per-function, per-file and per-module metrics for a set of files.
+#[derive(Debug, Default, Serialize)]
+pub struct MetricsReport {
+    pub functions: Vec<FunctionRow>,
+    pub files: Vec<FileRow>,
+    pub modules: Vec<ModuleRow>,
+}
+
+impl MetricsReport {
+    /// Measures every Rust file `walker` finds under `paths`. Files that
+    /// can't be read or parsed are skipped.
+    pub fn collect(paths: &[PathBuf], walker: &RustFileWalker) -> Self {
+        let mut files: Vec<PathBuf> = paths.iter().flat_map(|path| walker.walk(path)).collect();
+        files.sort();
+        files.dedup();
+
+        Self::from_sources(files.into_iter().filter_map(|path| {
+            let content = read_rust_file(&path).ok()?;
+            Some((path, content))
+        }))
+    }
+
+    /// Measures the given sources, keyed by the path they are reported as.
+    pub fn from_sources(sources: impl IntoIterator<Item = (PathBuf, String)>) -> Self {
+        let mut report = Self::default();
+
+        for (path, content) in sources {
+            if let Some((file, functions)) = measure_file(&path, &content) {
+                report.files.push(file);
+                report.functions.extend(functions);
+            }
+        }
+
+        report.modules = module_rows(&report.files);
+        report.sort_by(SortKey::Name);
+        report
+    }
+
+    /// Adds the files of `other` that this report doesn't have yet, for
+    /// paths measured separately because different configs apply to them.
+    pub fn merge(&mut self, other: MetricsReport) {
+        let known: HashSet<PathBuf> = self.files.iter().map(|row| row.file.clone()).collect();
+        self.files.extend(other.files.into_iter().filter(|row| !known.contains(&row.file)));
+        self.functions.extend(other.functions.into_iter().filter(|row| !known.contains(&row.file)));
+
+        self.modules = module_rows(&self.files);
+        self.sort_by(SortKey::Name);
+    }
+
+    pub fn sort_by(&mut self, key: SortKey) {
+        if key == SortKey::Name {
+            self.functions.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+            self.files.sort_by(|a, b| a.file.cmp(&b.file));
+            self.modules.sort_by(|a, b| a.module.cmp(&b.module));
+        } else {
+            // Stable sorts keep ties in name order
+            self.sort_by(SortKey::Name);
+            self.functions.sort_by_key(|row| std::cmp::Reverse(key.value(&row.metrics)));
+            self.files.sort_by_key(|row| std::cmp::Reverse(key.value(&row.metrics)));
+            self.modules.sort_by_key(|row| std::cmp::Reverse(key.value(&row.metrics)));
+        }
+    }
+
+    /// Keeps the first `count` rows of each table.
+    pub fn truncate(&mut self, count: usize) {
+        self.functions.truncate(count);
+        self.files.truncate(count);
+        self.modules.truncate(count);
+    }
+
+    /// Aligned tables, one per level.
+    pub fn write_text(&self, levels: &[Level], out: &mut impl Write) -> io::Result<()> {
+        let headers = ["LOC", "SLOC", "Cyclomatic", "Cognitive", "Nesting", "Params", "Unsafe", "Unwraps"];
+        let mut first = true;
+
+        for level in levels {
+            if !first {
+                writeln!(out)?;
+            }
+            first = false;
+
+            let (mut header, rows): (Vec<&str>, Vec<(Vec<String>, &Metrics)>) = match level {
+                Level::Function => (
+                    vec!["Function", "Location"],
+                    self.functions
+                        .iter()
+                        .map(|row| (vec![row.name.clone(), format!("{}:{}", row.file.display(), row.line)], &row.metrics))
+                        .collect(),
+                ),
+                Level::File => (
+                    vec!["File", "Functions"],
+                    self.files
+                        .iter()
+                        .map(|row| (vec![row.file.display().to_string(), row.functions.to_string()], &row.metrics))
+                        .collect(),
+                ),
+                Level::Module => (
+                    vec!["Module", "Files", "Functions"],
+                    self.modules
+                        .iter()
+                        .map(|row| (vec![row.module.clone(), row.files.to_string(), row.functions.to_string()], &row.metrics))
+                        .collect(),
+                ),
+            };
+            header.extend(headers);
+
+            let rows: Vec<Vec<String>> = rows
+                .into_iter()
+                .map(|(mut cells, metrics)| {
+                    cells.extend(metric_cells(metrics));
+                    cells
+                })
+                .collect();
+            write_table(out, &header, &rows)?;
+        }
+
+        Ok(())
+    }
+
+    /// One CSV table, with a `level` column telling functions, files and
+    /// modules apart.
+    pub fn write_csv(&self, levels: &[Level], out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "level,module,file,function,line,files,functions,loc,sloc,cyclomatic,cognitive,nesting,params,unsafe,unwraps"
+        )?;
+
+        let mut write_row = |cells: [String; 7], metrics: &Metrics| {
+            let cells: Vec<String> = cells.into_iter().chain(metric_cells(metrics)).map(|cell| csv_field(&cell)).collect();
+            writeln!(out, "{}", cells.join(","))
+        };
+
+        for level in levels {
+            match level {
+                Level::Function => {
+                    for row in &self.functions {
+                        let cells = [
+                            "function".to_string(),
+                            String::new(),
+                            row.file.display().to_string(),
+                            row.name.clone(),
+                            row.line.to_string(),
+                            String::new(),
+                            String::new(),
+                        ];
+                        write_row(cells, &row.metrics)?;
+                    }
+                }
+                Level::File => {
+                    for row in &self.files {
+                        let cells = [
+                            "file".to_string(),
+                            row.module.clone(),
+                            row.file.display().to_string(),
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                            row.functions.to_string(),
+                        ];
+                        write_row(cells, &row.metrics)?;
+                    }
+                }
+                Level::Module => {
+                    for row in &self.modules {
+                        let cells = [
+                            "module".to_string(),
+                            row.module.clone(),
+                            String::new(),
+                            String::new(),
+                            String::new(),
+                            row.files.to_string(),
+                            row.functions.to_string(),
+                        ];
+                        write_row(cells, &row.metrics)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The report as JSON, with only the tables for `levels`.
+    pub fn to_json(&self, levels: &[Level]) -> serde_json::Value {
+        let mut value = serde_json::Map::new();
+        for level in levels {
+            let (key, table) = match level {
+                Level::Function => ("functions", serde_json::to_value(&self.functions)),
+                Level::File => ("files", serde_json::to_value(&self.files)),
+                Level::Module => ("modules", serde_json::to_value(&self.modules)),
+            };
+            value.insert(key.to_string(), table.unwrap_or_default());
+        }
+        serde_json::Value::Object(value)
+    }
+}
+
+/// Measures one file and each function in it, or `None` if it doesn't parse.
+pub fn measure_file(path: &Path, content: &str) -> Option<(FileRow, Vec<FunctionRow>)> {
+    let syntax_tree = syn::parse_file(content).ok()?;
+    let loc = content.lines().count();
+    let comments = CommentIndex::new(content);
+
+    let mut functions = Vec::new();
+    let mut file_metrics = Metrics {
+        loc,
+        sloc: count_sloc(&comments, 1..=loc),
+        ..Metrics::default()
+    };
+
+    for function in file_items(&syntax_tree).functions {
+        let Some(block) = function.block else { continue };
+        let start = function.sig.fn_token.span.start().line;
+        let end = block.span().end().line.min(loc);
+
+        // Nested items are counted on their own
+        let mut usage = UsageCounter::new(true);
+        usage.visit_block(block);
+
+        let metrics = Metrics {
+            loc: (start..=end).count(),
+            sloc: count_sloc(&comments, start..=end),
+            cyclomatic: calculate_cyclomatic_complexity(block),
+            cognitive: calculate_cognitive_complexity(&function),
+            max_nesting: max_nesting_depth(block),
+            params: function
+                .sig
+                .inputs
+                .iter()
+                .filter(|input| matches!(input, syn::FnArg::Typed(_)))
+                .count(),
+            unsafe_blocks: usage.unsafe_blocks,
+            unwraps: usage.unwraps,
+        };
+        file_metrics.add_function(&metrics);
+
+        functions.push(FunctionRow {
+            name: function.name(),
+            file: path.to_path_buf(),
+            line: start,
+            metrics,
+        });
+    }
+
+    // Blocks and calls outside functions, such as in constants, count too
+    let mut usage = UsageCounter::new(false);
+    usage.visit_file(&syntax_tree);
+    file_metrics.unsafe_blocks = usage.unsafe_blocks;
+    file_metrics.unwraps = usage.unwraps;
+
+    let file = FileRow {
+        file: path.to_path_buf(),
+        module: module_path(path),
+        functions: functions.len(),
+        metrics: file_metrics,
+    };
+    Some((file, functions))
+}
+
+/// The module path of a file, starting with the crate it belongs to. The
+/// library and `main.rs` share a crate named after the package, so
+/// `src/rules/style.rs` is `app::rules::style`. Binaries under `src/bin/` and
+/// files outside `src/`, like integration tests, are crates of their own,
+/// named after the package and their root, such as `app/src/bin/tool`.
+pub fn module_path(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let (root, segments) = crate_module(&path);
+    let package_dir = package_dir(&root);
+    let package = package_dir.map(package_name).unwrap_or_else(|| "crate".to_string());
+
+    let crate_name = match package_dir.and_then(|dir| root.strip_prefix(dir).ok()) {
+        Some(relative) if relative != Path::new("src") => {
+            let relative = relative.with_extension("");
+            let parts: Vec<_> = relative.components().map(|part| part.as_os_str().to_string_lossy()).collect();
+            format!("{}/{}", package, parts.join("/"))
+        }
+        _ => package,
+    };
+
+    std::iter::once(crate_name).chain(segments).collect::<Vec<_>>().join("::")
+}
+
+/// The directory of the package a crate root belongs to: the nearest one with
+/// a `Cargo.toml`, or else the one holding `src/`, `tests/` and the like.
+fn package_dir(root: &Path) -> Option<&Path> {
+    root.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .or_else(|| match root.ancestors().find(|dir| dir.ends_with("src")) {
+            Some(src) => src.parent(),
+            None => root.parent()?.parent(),
+        })
+}
+
+/// The package's name from its manifest, or its directory's name, spelled
+/// the way it is as a crate name.
+fn package_name(dir: &Path) -> String {
+    let manifest = std::fs::read_to_string(dir.join("Cargo.toml")).ok();
+    let name = manifest
+        .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+        .and_then(|manifest| manifest.get("package")?.get("name")?.as_str().map(str::to_string))
+        .or_else(|| Some(dir.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "crate".to_string());
+    name.replace('-', "_")
+}
+
+/// Rolls files up into every module that contains them, so `app::rules`
+/// covers `app::rules::style` and `app` covers its whole crate.
+fn module_rows(files: &[FileRow]) -> Vec<ModuleRow> {
+    let mut modules: BTreeMap<String, ModuleRow> = BTreeMap::new();
+
+    for file in files {
+        let segments: Vec<&str> = file.module.split("::").collect();
+        let ancestors = (1..=segments.len()).map(|len| segments[..len].join("::"));
+
+        for module in ancestors {
+            let row = modules.entry(module.clone()).or_insert_with(|| ModuleRow {
+                module,
+                files: 0,
+                functions: 0,
+                metrics: Metrics::default(),
+            });
+            row.files += 1;
+            row.functions += file.functions;
+            row.metrics.add_file(&file.metrics);
+        }
+    }
+
+    modules.into_values().collect()
+}
+
+/// Counts `unsafe` blocks and `.unwrap()`/`.expect()` calls.
+struct UsageCounter {
+    skip_items: bool,
+    unsafe_blocks: usize,
+    unwraps: usize,
+}
+
+impl UsageCounter {
+    fn new(skip_items: bool) -> Self {
+        Self {
+            skip_items,
+            unsafe_blocks: 0,
+            unwraps: 0,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UsageCounter {
+    fn visit_item(&mut self, node: &'ast syn::Item) {
+        if !self.skip_items {
+            syn::visit::visit_item(self, node);
+        }
+    }
+
+    fn visit_expr_unsafe(&mut self, node: &'ast syn::ExprUnsafe) {
+        self.unsafe_blocks += 1;
+        syn::visit::visit_expr_unsafe(self, node);
+    }
+
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        if node.method == "unwrap" || node.method == "expect" {
+            self.unwraps += 1;
+        }
+        syn::visit::visit_expr_method_call(self, node);
+    }
+}
+
+fn metric_cells(metrics: &Metrics) -> [String; 8] {
+    [
+        metrics.loc,
+        metrics.sloc,
+        metrics.cyclomatic,
+        metrics.cognitive,
+        metrics.max_nesting,
+        metrics.params,
+        metrics.unsafe_blocks,
+        metrics.unwraps,
+    ]
+    .map(|value| value.to_string())
+}
+
+/// Writes rows under a header, names left-aligned and numbers right-aligned.
+fn write_table(out: &mut impl Write, header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    // Columns of numbers are right-aligned, header included
+    let numeric: Vec<bool> = (0..header.len())
+        .map(|column| column > 0 && !rows.is_empty() && rows.iter().all(|row| row[column].parse::<usize>().is_ok()))
+        .collect();
+
+    let format_row = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                if numeric[column] {
+                    format!("{:>width$}", cell, width = widths[column])
+                } else {
+                    format!("{:<width$}", cell, width = widths[column])
+                }
+            })
+            .collect();
+        cells.join("  ").trim_end().to_string()
+    };
+
+    writeln!(out, "{}", format_row(header.to_vec()))?;
+    for row in rows {
+        writeln!(out, "{}", format_row(row.iter().map(String::as_str).collect()))?;
+    }
+
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path(Path::new("/work/app/src/lib.rs")), "app");
+        assert_eq!(module_path(Path::new("/work/app/./src/main.rs")), "app");
+        assert_eq!(module_path(Path::new("/work/app/src/rules/mod.rs")), "app::rules");
+        assert_eq!(module_path(Path::new("/work/app/src/rules/style.rs")), "app::rules::style");
+        assert_eq!(module_path(Path::new("/work/app/src/bin/tool.rs")), "app/src/bin/tool");
+        assert_eq!(module_path(Path::new("/work/app/src/bin/tool/cli.rs")), "app/src/bin/tool::cli");
+        assert_eq!(module_path(Path::new("/work/app/tests/cli.rs")), "app/tests/cli");
+        assert_eq!(module_path(Path::new("/work/other/src/lib.rs")), "other");
+
+        // The manifest names the package
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().join("checkout");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"my-app\"\n").unwrap();
+        assert_eq!(module_path(&root.join("src/parse.rs")), "my_app::parse");
+        assert_eq!(module_path(&root.join("src/bin/tool.rs")), "my_app/src/bin/tool");
+    }
+
+    #[test]
+    fn test_metrics_report() {
+        let lib = "pub mod parse;\n\npub fn run() {\n    parse::parse(\"1\");\n}\n";
+        let parse = r#"/// Parses a number.
+pub fn parse(input: &str) -> u32 {
+    // Digits only
+    if input.is_empty() {
+        return 0;
+    }
+    /* multi-line
+       comment */
+    input.parse().unwrap()
+}
+
+fn read(ptr: *const u8, fallback: Option<u8>) -> u8 {
+    let value = unsafe { *ptr };
+    value.max(fallback.expect("fallback"))
+}
+"#;
+        let mut report = MetricsReport::from_sources([
+            (PathBuf::from("src/parse.rs"), parse.to_string()),
+            (PathBuf::from("src/lib.rs"), lib.to_string()),
+        ]);
+
+        let functions: Vec<(&str, usize, &Metrics)> = report
+            .functions
+            .iter()
+            .map(|row| (row.name.as_str(), row.line, &row.metrics))
+            .collect();
+        assert_eq!(functions[0].0, "run");
+        assert_eq!(functions[1], ("parse", 2, &Metrics {
+            loc: 9,
+            sloc: 6,
+            cyclomatic: 2,
+            cognitive: 1,
+            max_nesting: 1,
+            params: 1,
+            unsafe_blocks: 0,
+            unwraps: 1,
+        }));
+        assert_eq!((functions[2].2.params, functions[2].2.unsafe_blocks, functions[2].2.unwraps), (2, 1, 1));
+
+        let files: Vec<(&str, usize, usize, usize)> = report
+            .files
+            .iter()
+            .map(|row| (row.module.as_str(), row.functions, row.metrics.loc, row.metrics.cyclomatic))
+            .collect();
+        assert_eq!(files, [("cargo_fl", 1, 5, 1), ("cargo_fl::parse", 2, 15, 3)]);
+
+        let modules: Vec<(&str, usize, usize, usize)> = report
+            .modules
+            .iter()
+            .map(|row| (row.module.as_str(), row.files, row.functions, row.metrics.unwraps))
+            .collect();
+        assert_eq!(modules, [("cargo_fl", 2, 3, 2), ("cargo_fl::parse", 1, 2, 2)]);
+
+        report.sort_by(SortKey::Params);
+        assert_eq!(report.functions[0].name, "read");
+
+        let mut csv = Vec::new();
+        report.write_csv(&[Level::Module], &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.lines().any(|line| line == "module,cargo_fl::parse,,,,1,2,15,10,3,1,1,2,1,2"));
+
+        let json = report.to_json(&[Level::File]);
+        assert_eq!(json["files"][0]["module"], "cargo_fl::parse");
+        assert!(json.get("functions").is_none());
+
+        let mut text = Vec::new();
+        report.write_text(&[Level::Function], &mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.starts_with("Function  Location         LOC  SLOC  Cyclomatic"));
+        assert!(text.contains("\nread      src/parse.rs:12    4     4           1"));
+
+        // Files measured twice under different configs are counted once
+        let mut merged = MetricsReport::from_sources([(PathBuf::from("src/lib.rs"), lib.to_string())]);
+        merged.merge(MetricsReport::from_sources([
+            (PathBuf::from("src/lib.rs"), lib.to_string()),
+            (PathBuf::from("src/parse.rs"), parse.to_string()),
+        ]));
+        assert_eq!(merged.files.len(), 2);
+        assert_eq!(merged.functions.len(), 3);
+        assert_eq!(merged.modules[0].files, 2);
+
+        // `self` isn't a parameter callers pass
+        let (_, methods) = measure_file(Path::new("src/point.rs"), "impl Point {\n    fn scale(&self, by: f64) {}\n}\n").unwrap();
+        assert_eq!(methods[0].metrics.params, 1);
+    }
+}
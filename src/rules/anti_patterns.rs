@@ -1,4 +1,4 @@
-use crate::rules::{Issue, Location, Rule, RuleContext, Severity, Fix, Replacement};
+use crate::rules::{Applicability, Issue, Location, Rule, RuleContext, Severity, Fix, Replacement};
 use syn::visit::Visit;
 use syn::{Expr, ExprMethodCall, ExprCall, Pat, PatIdent, ExprMatch, Type, ExprForLoop, ExprIf, ExprLet, BinOp};
 use syn::spanned::Spanned;
@@ -11,7 +11,7 @@ impl Rule for AntiPatternsRule {
     }
 
     fn check(&self, ctx: &mut RuleContext) {
-        let syntax_tree = ctx.syntax_tree.clone();
+        let syntax_tree = ctx.syntax_tree().clone();
         let mut visitor = AntiPatternsVisitor::new(ctx);
         visitor.visit_file(&syntax_tree);
     }
@@ -38,6 +38,7 @@ impl<'a> AntiPatternsVisitor<'a> {
                 end_column: Some(col + 10),
             },
             fix,
+            related: Vec::new(),
         });
     }
 
@@ -53,10 +54,13 @@ impl<'a> AntiPatternsVisitor<'a> {
                 Some(Fix {
                     description: "Consider removing .clone() if borrowing is sufficient".to_string(),
                     replacements: vec![Replacement {
-                        start: 0,
-                        end: 0,
-                        text: "// TODO: Review if clone is necessary".to_string(),
+                        start: method_call.dot_token.span.byte_range().start,
+                        end: method_call.paren_token.span.close().byte_range().end,
+                        text: String::new(),
+                        file: None,
                     }],
+                    // Whether a borrow suffices depends on how the value is used
+                    applicability: Applicability::Unsafe,
                 })
             );
         }
@@ -70,7 +74,8 @@ impl<'a> AntiPatternsVisitor<'a> {
             "to_string" => {
                 // Check if called on string literal
                 if let Expr::Lit(lit) = method_call.receiver.as_ref() {
-                    if let syn::Lit::Str(_) = &lit.lit {
+                    if let syn::Lit::Str(str_lit) = &lit.lit {
+                        let literal = str_lit.token().to_string();
                         self.report_antipattern(
                             line,
                             col,
@@ -78,10 +83,12 @@ impl<'a> AntiPatternsVisitor<'a> {
                             Some(Fix {
                                 description: "Replace with String::from()".to_string(),
                                 replacements: vec![Replacement {
-                                    start: 0,
-                                    end: 0,
-                                    text: "String::from(...)".to_string(),
+                                    start: str_lit.span().byte_range().start,
+                                    end: method_call.paren_token.span.close().byte_range().end,
+                                    text: format!("String::from({})", literal),
+                                    file: None,
                                 }],
+                                applicability: Applicability::Safe,
                             })
                         );
                     }
@@ -126,22 +133,34 @@ impl<'a> AntiPatternsVisitor<'a> {
                         end_column: Some(col + method_name.len()),
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
             "into_iter" => {
                 // Check if called on reference
-                if let Expr::Reference(_) = method_call.receiver.as_ref() {
+                let receiver = match method_call.receiver.as_ref() {
+                    Expr::Paren(paren) => paren.expr.as_ref(),
+                    receiver => receiver,
+                };
+                if let Expr::Reference(reference) = receiver {
+                    let collection = self.ctx.content
+                        .get(reference.expr.span().byte_range())
+                        .unwrap_or_default()
+                        .to_string();
+                    let method = if reference.mutability.is_some() { "iter_mut" } else { "iter" };
                     self.report_antipattern(
                         line,
                         col,
                         "Use `.iter()` instead of `(&collection).into_iter()`",
                         Some(Fix {
-                            description: "Replace with .iter()".to_string(),
+                            description: format!("Replace with .{}()", method),
                             replacements: vec![Replacement {
-                                start: 0,
-                                end: 0,
-                                text: ".iter()".to_string(),
+                                start: method_call.receiver.span().byte_range().start,
+                                end: method_call.paren_token.span.close().byte_range().end,
+                                text: format!("{}.{}()", collection, method),
+                                file: None,
                             }],
+                            applicability: Applicability::Safe,
                         })
                     );
                 }
@@ -168,6 +187,7 @@ impl<'a> AntiPatternsVisitor<'a> {
                         end_column: Some(col + method_name.len()),
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
             "map" => {
@@ -205,10 +225,12 @@ impl<'a> Visit<'a> for AntiPatternsVisitor<'a> {
                                     Some(Fix {
                                         description: "Replace with .first()".to_string(),
                                         replacements: vec![Replacement {
-                                            start: 0,
-                                            end: 0,
-                                            text: ".first()".to_string(),
+                                            start: method_call.method.span().byte_range().start,
+                                            end: method_call.paren_token.span.close().byte_range().end,
+                                            text: "first()".to_string(),
+                                            file: None,
                                         }],
+                                        applicability: Applicability::Safe,
                                     })
                                 );
                             }
@@ -240,6 +262,7 @@ impl<'a> Visit<'a> for AntiPatternsVisitor<'a> {
                         end_column: Some(col + 5),
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
         }
@@ -280,6 +303,7 @@ impl<'a> Visit<'a> for AntiPatternsVisitor<'a> {
                         end_column: Some(col + 5),
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
         }
@@ -304,6 +328,7 @@ impl<'a> Visit<'a> for AntiPatternsVisitor<'a> {
                         end_column: Some(col + 3),
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
         }
@@ -311,4 +336,38 @@ impl<'a> Visit<'a> for AntiPatternsVisitor<'a> {
         // Continue visiting
         syn::visit::visit_expr_for_loop(self, for_loop);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_anti_patterns_allowed_in_tests() {
+        let content = r#"
+fn name() -> String {
+    "library".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn fixture() -> String {
+        "fixture".to_string()
+    }
+}
+"#;
+        let check = |path: &str, allowed: &[&str]| {
+            let syntax_tree = syn::parse_file(content).unwrap();
+            let mut ctx = RuleContext::new(path.into(), content.to_string(), syntax_tree).allow_in_tests(allowed);
+            AntiPatternsRule.check(&mut ctx);
+            ctx.into_issues().iter().map(|issue| issue.location.line).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(check("src/lib.rs", &["anti_patterns"]), [3]);
+        assert_eq!(check("src/lib.rs", &[]), [3, 10]);
+        assert!(check("tests/fixtures.rs", &["anti_patterns"]).is_empty());
+        // Only the rules given are exempt
+        assert_eq!(check("tests/fixtures.rs", &["unwrap_usage"]), [3, 10]);
+    }
+}
@@ -0,0 +1,428 @@
+use proc_macro2::LineColumn;
+
+/// `//` or `/* */`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    Line,
+    Block,
+}
+
+/// Which item a doc comment documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocStyle {
+    /// `///` and `/** */`, documenting the item that follows
+    Outer,
+    /// `//!` and `/*! */`, documenting the enclosing item
+    Inner,
+}
+
+/// A comment in the source, which `syn` drops unless it is a doc comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    /// `None` for plain comments
+    pub doc: Option<DocStyle>,
+    /// Everything between the delimiters, e.g. ` SAFETY: ok` for
+    /// `// SAFETY: ok`, and ` b ` for `/*! b */`
+    pub text: String,
+    /// 1-based line and 0-based character column of the first delimiter,
+    /// as in `syn` spans
+    pub start: LineColumn,
+    /// Just past the closing `*/`, or the end of the line
+    pub end: LineColumn,
+    /// Start of the first token after the comment
+    next_code: Option<LineColumn>,
+    /// Whether there are tokens before it on its first line
+    follows_code: bool,
+}
+
+impl Comment {
+    /// The text without the surrounding whitespace.
+    pub fn body(&self) -> &str {
+        self.text.trim()
+    }
+    
+    pub fn is_doc(&self) -> bool {
+        self.doc.is_some()
+    }
+}
+
+/// Every comment in a file in source order, with lookups by position. Built
+/// by lexing the source text, so comment markers inside string and character
+/// literals aren't mistaken for comments.
+#[derive(Debug, Clone, Default)]
+pub struct CommentIndex {
+    comments: Vec<Comment>,
+    /// Lines with tokens on them, indexed by 1-based line
+    code_lines: Vec<bool>,
+}
+
+impl CommentIndex {
+    pub fn new(content: &str) -> Self {
+        Lexer::new(content).run()
+    }
+    
+    pub fn iter(&self) -> std::slice::Iter<'_, Comment> {
+        self.comments.iter()
+    }
+    
+    /// Comments starting on a 1-based line.
+    pub fn on_line(&self, line: usize) -> impl Iterator<Item = &Comment> {
+        let first = self.comments.partition_point(|comment| comment.start.line < line);
+        self.comments[first..].iter().take_while(move |comment| comment.start.line == line)
+    }
+    
+    /// The comments directly before `position`, with nothing but whitespace
+    /// between them and it, in source order. Pass the start of a span to find
+    /// the comments written above an item, statement or expression. Comments
+    /// trailing code on an earlier line belong to that code, and end the run.
+    pub fn preceding(&self, position: LineColumn) -> &[Comment] {
+        let end = self.comments.partition_point(|comment| comment.end <= position);
+        let mut start = end;
+        while let Some(comment) = start.checked_sub(1).map(|index| &self.comments[index]) {
+            let separated = comment.next_code.is_some_and(|code| code < position);
+            let trailing = comment.follows_code && comment.start.line != position.line;
+            if separated || trailing {
+                break;
+            }
+            start -= 1;
+        }
+        &self.comments[start..end]
+    }
+    
+    /// Whether a 1-based line has anything besides comments and whitespace.
+    pub fn line_has_code(&self, line: usize) -> bool {
+        self.code_lines.get(line).copied().unwrap_or(false)
+    }
+    
+    /// Whether a 1-based line has a comment and nothing else.
+    pub fn is_comment_line(&self, line: usize) -> bool {
+        !self.line_has_code(line)
+            && self
+                .comments
+                .iter()
+                .any(|comment| (comment.start.line..=comment.end.line).contains(&line))
+    }
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+    comments: Vec<Comment>,
+    code_lines: Vec<bool>,
+    /// Comments still waiting for the next token
+    pending: usize,
+}
+
+impl Lexer {
+    fn new(content: &str) -> Self {
+        Self {
+            chars: content.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 0,
+            comments: Vec::new(),
+            code_lines: vec![false; content.lines().count() + 2],
+            pending: 0,
+        }
+    }
+    
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+    
+    fn position(&self) -> LineColumn {
+        LineColumn {
+            line: self.line,
+            column: self.column,
+        }
+    }
+    
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+    
+    /// Consumes one character of a token, marking its line as code.
+    fn bump_code(&mut self) -> Option<char> {
+        if let Some(seen) = self.code_lines.get_mut(self.line) {
+            *seen = true;
+        }
+        self.bump()
+    }
+    
+    /// Records where the token at the current position starts for the
+    /// comments before it.
+    fn start_token(&mut self) {
+        let position = self.position();
+        for comment in &mut self.comments[self.pending..] {
+            comment.next_code = Some(position);
+        }
+        self.pending = self.comments.len();
+    }
+    
+    fn run(mut self) -> CommentIndex {
+        while let Some(c) = self.peek(0) {
+            match c {
+                '/' if self.peek(1) == Some('/') => self.line_comment(),
+                '/' if self.peek(1) == Some('*') => self.block_comment(),
+                c if c.is_whitespace() => {
+                    self.bump();
+                }
+                _ => {
+                    self.start_token();
+                    self.token();
+                }
+            }
+        }
+        
+        CommentIndex {
+            comments: self.comments,
+            code_lines: self.code_lines,
+        }
+    }
+    
+    fn line_comment(&mut self) {
+        let start = self.position();
+        self.bump();
+        self.bump();
+        
+        // `////` is a plain comment
+        let doc = match (self.peek(0), self.peek(1)) {
+            (Some('/'), next) if next != Some('/') => Some(DocStyle::Outer),
+            (Some('!'), _) => Some(DocStyle::Inner),
+            _ => None,
+        };
+        if doc.is_some() {
+            self.bump();
+        }
+        
+        let mut text = String::new();
+        while let Some(c) = self.peek(0) {
+            if c == '\n' {
+                break;
+            }
+            text.push(c);
+            self.bump();
+        }
+        let text = text.strip_suffix('\r').map(str::to_string).unwrap_or(text);
+        
+        self.push_comment(CommentKind::Line, doc, text, start);
+    }
+    
+    fn block_comment(&mut self) {
+        let start = self.position();
+        self.bump();
+        self.bump();
+        
+        // `/**/` and `/***` are plain comments
+        let doc = match (self.peek(0), self.peek(1)) {
+            (Some('*'), Some(next)) if next != '*' && next != '/' => Some(DocStyle::Outer),
+            (Some('!'), _) => Some(DocStyle::Inner),
+            _ => None,
+        };
+        if doc.is_some() {
+            self.bump();
+        }
+        
+        // Block comments nest
+        let mut depth = 1;
+        let mut text = String::new();
+        while let Some(c) = self.peek(0) {
+            if c == '/' && self.peek(1) == Some('*') {
+                depth += 1;
+                text.push_str("/*");
+                self.bump();
+                self.bump();
+            } else if c == '*' && self.peek(1) == Some('/') {
+                self.bump();
+                self.bump();
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+                text.push_str("*/");
+            } else {
+                text.push(c);
+                self.bump();
+            }
+        }
+        
+        self.push_comment(CommentKind::Block, doc, text, start);
+    }
+    
+    fn push_comment(&mut self, kind: CommentKind, doc: Option<DocStyle>, text: String, start: LineColumn) {
+        let end = self.position();
+        self.comments.push(Comment {
+            kind,
+            doc,
+            text,
+            start,
+            end,
+            next_code: None,
+            follows_code: self.code_lines.get(start.line).copied().unwrap_or(false),
+        });
+    }
+    
+    /// Consumes a token that isn't a comment: a literal, identifier, lifetime
+    /// or punctuation.
+    fn token(&mut self) {
+        let Some(c) = self.peek(0) else { return };
+        
+        if c == '"' {
+            self.bump_code();
+            self.string_body();
+        } else if c == '\'' {
+            self.quote();
+        } else if c == '_' || c.is_alphanumeric() {
+            let mut word = String::new();
+            while let Some(c) = self.peek(0).filter(|c| *c == '_' || c.is_alphanumeric()) {
+                word.push(c);
+                self.bump_code();
+            }
+            self.literal_after_prefix(&word);
+        } else {
+            self.bump_code();
+        }
+    }
+    
+    /// Handles the string or character literal following a prefix such as
+    /// `b`, `r` or `br`, if there is one.
+    fn literal_after_prefix(&mut self, word: &str) {
+        match (word, self.peek(0)) {
+            ("b" | "c", Some('"')) => {
+                self.bump_code();
+                self.string_body();
+            }
+            ("b", Some('\'')) => self.quote(),
+            ("r" | "br" | "cr", Some('"' | '#')) => self.raw_string(),
+            _ => {}
+        }
+    }
+    
+    /// Consumes the rest of a string literal after its opening quote.
+    fn string_body(&mut self) {
+        while let Some(c) = self.bump_code() {
+            match c {
+                '\\' => {
+                    self.bump_code();
+                }
+                '"' => break,
+                _ => {}
+            }
+        }
+    }
+    
+    /// Consumes `r#"..."#` from the first `#` or `"`, or the `#` of a raw
+    /// identifier such as `r#type`.
+    fn raw_string(&mut self) {
+        let hashes = (0..).take_while(|&offset| self.peek(offset) == Some('#')).count();
+        if self.peek(hashes) != Some('"') {
+            // A raw identifier
+            return;
+        }
+        for _ in 0..=hashes {
+            self.bump_code();
+        }
+        
+        while let Some(c) = self.bump_code() {
+            if c == '"' && (0..hashes).all(|offset| self.peek(offset) == Some('#')) {
+                for _ in 0..hashes {
+                    self.bump_code();
+                }
+                break;
+            }
+        }
+    }
+    
+    /// Consumes a character literal, or the quote of a lifetime or label.
+    fn quote(&mut self) {
+        self.bump_code();
+        match (self.peek(0), self.peek(1)) {
+            (Some('\\'), _) => {
+                while let Some(c) = self.bump_code() {
+                    if c == '\\' {
+                        self.bump_code();
+                    } else if c == '\'' {
+                        break;
+                    }
+                }
+            }
+            (Some(_), Some('\'')) => {
+                self.bump_code();
+                self.bump_code();
+            }
+            // A lifetime, whose name is lexed as an identifier
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn at(line: usize, column: usize) -> LineColumn {
+        LineColumn { line, column }
+    }
+    
+    #[test]
+    fn test_comment_lexing() {
+        let source = r####"//! Crate docs
+/// Outer doc
+//// Not a doc
+fn main() { // trailing
+    let url = "http://example.com/*";
+    let raw = r#"// "not" a comment"#;
+    let c = '"'; let q = '\''; /* after chars */
+    let life: &'static str = "";
+    /* outer /* nested */ still outer */
+    /** block doc */
+    /**/
+}
+"####;
+        let index = CommentIndex::new(source);
+        let comments: Vec<(usize, CommentKind, Option<DocStyle>, &str)> = index
+            .iter()
+            .map(|comment| (comment.start.line, comment.kind, comment.doc, comment.text.as_str()))
+            .collect();
+        assert_eq!(comments, [
+            (1, CommentKind::Line, Some(DocStyle::Inner), " Crate docs"),
+            (2, CommentKind::Line, Some(DocStyle::Outer), " Outer doc"),
+            (3, CommentKind::Line, None, "// Not a doc"),
+            (4, CommentKind::Line, None, " trailing"),
+            (7, CommentKind::Block, None, " after chars "),
+            (9, CommentKind::Block, None, " outer /* nested */ still outer "),
+            (10, CommentKind::Block, Some(DocStyle::Outer), " block doc "),
+            (11, CommentKind::Block, None, ""),
+        ]);
+        
+        assert_eq!(index.iter().nth(4).map(|comment| (comment.start, comment.end)), Some((at(7, 31), at(7, 48))));
+        assert!(index.line_has_code(4));
+        assert!(!index.line_has_code(9));
+        assert!(index.is_comment_line(9));
+        assert!(!index.is_comment_line(4));
+        assert_eq!(index.on_line(4).count(), 1);
+    }
+    
+    #[test]
+    fn test_preceding_comments() {
+        let source = "fn f() {\n    // first\n    /* second */\n\n    // SAFETY: third\n    unsafe { g() } // after\n    /* inline */ h();\n}\n";
+        let index = CommentIndex::new(source);
+        
+        let bodies = |position| -> Vec<&str> { index.preceding(position).iter().map(Comment::body).collect() };
+        assert_eq!(bodies(at(6, 4)), ["first", "second", "SAFETY: third"]);
+        assert_eq!(bodies(at(7, 17)), ["inline"]);
+        assert!(bodies(at(6, 13)).is_empty());
+        assert!(bodies(at(1, 0)).is_empty());
+    }
+}
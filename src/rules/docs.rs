@@ -1,6 +1,16 @@
 use super::*;
+use crate::autofix::DocTemplateGenerator;
+use syn::spanned::Spanned;
 
-pub struct MissingDocsRule;
+pub struct MissingDocsRule {
+    add_doc_templates: bool,
+}
+
+impl MissingDocsRule {
+    pub fn new(add_doc_templates: bool) -> Self {
+        Self { add_doc_templates }
+    }
+}
 
 impl Rule for MissingDocsRule {
     fn name(&self) -> &'static str {
@@ -10,81 +20,83 @@ impl Rule for MissingDocsRule {
     fn check(&self, ctx: &mut RuleContext) {
         // Collect items to check first to avoid borrowing issues
         let mut issues_to_report = Vec::new();
+        let generator = DocTemplateGenerator::new();
+        
+        let items = ctx.items();
+        
+        for item in &items.items {
+            let (kind, ident, attrs) = match item {
+                syn::Item::Struct(s) if is_pub(&s.vis) => ("struct", &s.ident, &s.attrs),
+                syn::Item::Enum(e) if is_pub(&e.vis) => ("enum", &e.ident, &e.attrs),
+                syn::Item::Trait(t) if is_pub(&t.vis) => ("trait", &t.ident, &t.attrs),
+                _ => continue,
+            };
+            
+            if has_doc_comment(attrs) {
+                continue;
+            }
+            
+            let (line, col) = ctx.line_col(ident.span());
+            
+            let fix = self.add_doc_templates.then(|| {
+                // Docs go above any attributes, at the item's own indentation
+                let (item_line, _) = ctx.line_col(item.span());
+                let insert_at = ctx.line_start_offset(item_line);
+                let indent = ctx.line_indent(item_line);
+                generator.generate_type_doc_fix(kind, &ident.to_string(), insert_at, indent)
+            });
+            
+            issues_to_report.push(Issue {
+                rule: self.name().to_string(),
+                severity: Severity::Warning,
+                message: format!("Missing documentation for public {} '{}'", kind, ident),
+                location: Location {
+                    line,
+                    column: col,
+                    end_line: None,
+                    end_column: None,
+                },
+                fix,
+                related: Vec::new(),
+            });
+        }
         
-        for item in &ctx.syntax_tree.items {
-            match item {
-                syn::Item::Fn(f) if is_pub(&f.vis) => {
-                    if !has_doc_comment(&f.attrs) {
-                        let (line, col) = ctx.line_col(f.sig.ident.span());
-                        issues_to_report.push(Issue {
-                            rule: self.name().to_string(),
-                            severity: Severity::Warning,
-                            message: format!("Missing documentation for public function '{}'", f.sig.ident),
-                            location: Location {
-                                line,
-                                column: col,
-                                end_line: None,
-                                end_column: None,
-                            },
-                            fix: None,
-                        });
-                    }
-                }
-                syn::Item::Struct(s) if is_pub(&s.vis) => {
-                    if !has_doc_comment(&s.attrs) {
-                        let (line, col) = ctx.line_col(s.ident.span());
-                        issues_to_report.push(Issue {
-                            rule: self.name().to_string(),
-                            severity: Severity::Warning,
-                            message: format!("Missing documentation for public struct '{}'", s.ident),
-                            location: Location {
-                                line,
-                                column: col,
-                                end_line: None,
-                                end_column: None,
-                            },
-                            fix: None,
-                        });
-                    }
-                }
-                syn::Item::Enum(e) if is_pub(&e.vis) => {
-                    if !has_doc_comment(&e.attrs) {
-                        let (line, col) = ctx.line_col(e.ident.span());
-                        issues_to_report.push(Issue {
-                            rule: self.name().to_string(),
-                            severity: Severity::Warning,
-                            message: format!("Missing documentation for public enum '{}'", e.ident),
-                            location: Location {
-                                line,
-                                column: col,
-                                end_line: None,
-                                end_column: None,
-                            },
-                            fix: None,
-                        });
-                    }
-                }
-                syn::Item::Trait(t) if is_pub(&t.vis) => {
-                    if !has_doc_comment(&t.attrs) {
-                        let (line, col) = ctx.line_col(t.ident.span());
-                        issues_to_report.push(Issue {
-                            rule: self.name().to_string(),
-                            severity: Severity::Warning,
-                            message: format!("Missing documentation for public trait '{}'", t.ident),
-                            location: Location {
-                                line,
-                                column: col,
-                                end_line: None,
-                                end_column: None,
-                            },
-                            fix: None,
-                        });
-                    }
-                }
-                _ => {}
+        for function in &items.functions {
+            // Trait implementations are documented on the trait
+            if function.context == FnContext::TraitImpl
+                || !is_pub(function.vis)
+                || has_doc_comment(function.attrs)
+            {
+                continue;
             }
+            
+            let kind = if function.owner.is_some() { "method" } else { "function" };
+            let (line, col) = ctx.line_col(function.sig.ident.span());
+            
+            let fix = self.add_doc_templates.then(|| {
+                let (item_line, _) = ctx.line_col(function.span);
+                let insert_at = ctx.line_start_offset(item_line);
+                let indent = ctx.line_indent(item_line);
+                generator.generate_function_doc_fix(function.sig, function.block, insert_at, indent)
+            });
+            
+            issues_to_report.push(Issue {
+                rule: self.name().to_string(),
+                severity: Severity::Warning,
+                message: format!("Missing documentation for public {} '{}'", kind, function.name()),
+                location: Location {
+                    line,
+                    column: col,
+                    end_line: None,
+                    end_column: None,
+                },
+                fix,
+                related: Vec::new(),
+            });
         }
         
+        issues_to_report.sort_by_key(|issue| (issue.location.line, issue.location.column));
+        
         // Report all issues
         for issue in issues_to_report {
             ctx.report(issue);
@@ -92,10 +104,82 @@ impl Rule for MissingDocsRule {
     }
 }
 
-fn is_pub(vis: &syn::Visibility) -> bool {
+pub fn is_pub(vis: &syn::Visibility) -> bool {
     matches!(vis, syn::Visibility::Public(_))
 }
 
 fn has_doc_comment(attrs: &[syn::Attribute]) -> bool {
     attrs.iter().any(|attr| attr.path().is_ident("doc"))
-}
\ No newline at end of file
+}
+
+/// Whether the doc comments in `attrs` have a Markdown heading such as
+/// `# Safety` or `## Panics` (`heading` without the `#`s).
+pub fn has_doc_section(attrs: &[syn::Attribute], heading: &str) -> bool {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(doc) if doc.path.is_ident("doc") => match &doc.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(text), .. }) => Some(text.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .any(|text| {
+            text.lines().any(|line| {
+                let line = line.trim();
+                line.starts_with('#') && line.trim_start_matches('#').trim().eq_ignore_ascii_case(heading)
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_missing_docs_on_nested_items() {
+        let content = r#"
+/// A parser.
+pub struct Parser;
+
+impl Parser {
+    pub fn parse(&self) {}
+    fn helper(&self) {}
+}
+
+impl Default for Parser {
+    fn default() -> Self { Parser }
+}
+
+/// Visits things.
+pub trait Visit {
+    fn visit(&self);
+}
+
+pub mod inner {
+    pub enum Kind { A }
+    
+    pub fn build() {}
+}
+"#;
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        MissingDocsRule::new(true).check(&mut ctx);
+        
+        let messages: Vec<(usize, &str)> = ctx
+            .issues
+            .iter()
+            .map(|issue| (issue.location.line, issue.message.as_str()))
+            .collect();
+        assert_eq!(messages, [
+            (6, "Missing documentation for public method 'Parser::parse'"),
+            (16, "Missing documentation for public method 'Visit::visit'"),
+            (20, "Missing documentation for public enum 'Kind'"),
+            (22, "Missing documentation for public function 'build'"),
+        ]);
+        
+        // Templates are inserted above the method at its own indentation
+        let fix = ctx.issues[0].fix.as_ref().unwrap();
+        assert!(fix.replacements[0].text.starts_with("    ///"));
+    }
+}
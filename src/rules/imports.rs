@@ -10,13 +10,15 @@ impl Rule for ImportOrderRule {
     }
     
     fn check(&self, ctx: &mut RuleContext) {
-        let mut std_imports = vec![];
-        let mut external_imports = vec![];
-        let mut local_imports = vec![];
         let mut issues_to_report = Vec::new();
         
-        for item in &ctx.syntax_tree.items {
-            if let syn::Item::Use(use_item) = item {
+        // Imports are grouped within each module or block that declares them
+        for scope in &ctx.items().scopes {
+            let mut std_imports = vec![];
+            let mut external_imports = vec![];
+            let mut local_imports = vec![];
+            
+            for use_item in scope.uses() {
                 let path = use_path_to_string(&use_item.tree);
                 let (line, col) = ctx.line_col(use_item.span());
                 
@@ -28,40 +30,44 @@ impl Rule for ImportOrderRule {
                     external_imports.push((path, line, col));
                 }
             }
-        }
-        
-        // Check if imports are grouped correctly
-        let mut last_std_line = 0;
-        let mut last_external_line = 0;
-        
-        for (_, line, _) in &std_imports {
-            last_std_line = last_std_line.max(*line);
-        }
-        
-        for (_, line, col) in &external_imports {
-            if *line < last_std_line {
-                issues_to_report.push(Issue {
-                    rule: self.name().to_string(),
-                    severity: Severity::Info,
-                    message: "External imports should come after standard library imports".to_string(),
-                    location: Location {
-                        line: *line,
-                        column: *col,
-                        end_line: None,
-                        end_column: None,
-                    },
-                    fix: None,
-                });
-            }
-            last_external_line = last_external_line.max(*line);
-        }
-        
-        for (_, line, col) in &local_imports {
-            if *line < last_external_line {
+            
+            // Check if imports are grouped correctly, pointing misplaced imports
+            // at the last import of the group they should follow
+            let last_std = std_imports.iter().max_by_key(|(_, line, _)| *line);
+            let last_external = external_imports.iter().max_by_key(|(_, line, _)| *line);
+            let last_std_line = last_std.map_or(0, |(_, line, _)| *line);
+            let last_external_line = last_external.map_or(0, |(_, line, _)| *line);
+            
+            let misplaced = external_imports
+                .iter()
+                .filter(|(_, line, _)| *line < last_std_line)
+                .map(|import| (import, last_std, "External imports should come after standard library imports"));
+            let misplaced = misplaced.chain(
+                local_imports
+                    .iter()
+                    .filter(|(_, line, _)| *line < last_external_line)
+                    .map(|import| (import, last_external, "Local imports should come after external crate imports")),
+            );
+            
+            for ((_, line, col), follow, message) in misplaced {
+                let related = follow
+                    .map(|(path, line, col)| RelatedLocation {
+                        location: Location {
+                            line: *line,
+                            column: *col,
+                            end_line: None,
+                            end_column: None,
+                        },
+                        message: format!("Move the import below `use {}`", path),
+                        file: None,
+                    })
+                    .into_iter()
+                    .collect();
+                
                 issues_to_report.push(Issue {
                     rule: self.name().to_string(),
                     severity: Severity::Info,
-                    message: "Local imports should come after external crate imports".to_string(),
+                    message: message.to_string(),
                     location: Location {
                         line: *line,
                         column: *col,
@@ -69,6 +75,7 @@ impl Rule for ImportOrderRule {
                         end_column: None,
                     },
                     fix: None,
+                    related,
                 });
             }
         }
@@ -88,39 +95,73 @@ impl Rule for UnusedImportRule {
     }
     
     fn check(&self, ctx: &mut RuleContext) {
-        let mut imports = HashMap::new();
-        let mut used_idents = HashSet::new();
         let mut issues_to_report = Vec::new();
         
-        // Collect all imports
-        for item in &ctx.syntax_tree.items {
-            if let syn::Item::Use(use_item) = item {
-                collect_use_tree_idents(&use_item.tree, &mut imports, ctx);
-            }
-        }
-        
-        // Collect all used identifiers
         struct IdentCollector<'a> {
             used: &'a mut HashSet<String>,
+            /// Inside a nested module, which has a namespace of its own
+            nested: bool,
         }
         
         impl<'ast> Visit<'ast> for IdentCollector<'_> {
             fn visit_ident(&mut self, ident: &'ast syn::Ident) {
-                self.used.insert(ident.to_string());
+                if !self.nested {
+                    self.used.insert(ident.to_string());
+                }
             }
-        }
-        
-        let mut collector = IdentCollector { used: &mut used_idents };
-        for item in &ctx.syntax_tree.items {
-            // Skip the use statements themselves
-            if !matches!(item, syn::Item::Use(_)) {
-                collector.visit_item(item);
+            
+            fn visit_path(&mut self, path: &'ast syn::Path) {
+                // A nested module only reaches the scope's imports as `super::name`
+                if self.nested && path.segments.first().is_some_and(|segment| segment.ident == "super") {
+                    if let Some(segment) = path.segments.iter().find(|segment| segment.ident != "super") {
+                        self.used.insert(segment.ident.to_string());
+                    }
+                }
+                syn::visit::visit_path(self, path);
+            }
+            
+            fn visit_use_tree(&mut self, tree: &'ast syn::UseTree) {
+                if self.nested {
+                    if let syn::UseTree::Path(path) = tree {
+                        if path.ident == "super" {
+                            collect_super_imports(&path.tree, self.used);
+                        }
+                    }
+                }
+                syn::visit::visit_use_tree(self, tree);
+            }
+            
+            fn visit_item_mod(&mut self, module: &'ast syn::ItemMod) {
+                // After `use super::*` every name in the module may be one of ours
+                let outer = std::mem::replace(&mut self.nested, !imports_super_glob(module));
+                syn::visit::visit_item_mod(self, module);
+                self.nested = outer;
             }
         }
         
-        // Report unused imports
-        for (name, (line, col)) in imports {
-            if !used_idents.contains(&name) {
+        // An import is visible in the module or block that declares it,
+        // including nested blocks. Nested modules only see it through `super`.
+        for scope in &ctx.items().scopes {
+            let mut imports = HashMap::new();
+            let mut used_idents = HashSet::new();
+            
+            for use_item in scope.uses() {
+                collect_use_tree_idents(&use_item.tree, &mut imports, ctx);
+            }
+            if imports.is_empty() {
+                continue;
+            }
+            
+            // Collect used identifiers, skipping the scope's own use statements
+            scope.visit_body(&mut IdentCollector { used: &mut used_idents, nested: false });
+            
+            let mut unused: Vec<_> = imports
+                .into_iter()
+                .filter(|(name, _)| !used_idents.contains(name))
+                .collect();
+            unused.sort_by_key(|(_, position)| *position);
+            
+            for (name, (line, col)) in unused {
                 issues_to_report.push(Issue {
                     rule: self.name().to_string(),
                     severity: Severity::Warning,
@@ -134,7 +175,9 @@ impl Rule for UnusedImportRule {
                     fix: Some(Fix {
                         description: "Remove unused import".to_string(),
                         replacements: vec![], // Would calculate actual removal
+                        applicability: Applicability::Safe,
                     }),
+                    related: Vec::new(),
                 });
             }
         }
@@ -183,4 +226,108 @@ fn collect_use_tree_idents(
         }
         _ => {}
     }
-}
\ No newline at end of file
+}
+
+/// Records the names a `use super::...` tree takes from the parent scope
+fn collect_super_imports(tree: &syn::UseTree, used: &mut HashSet<String>) {
+    match tree {
+        syn::UseTree::Path(p) => {
+            used.insert(p.ident.to_string());
+        }
+        syn::UseTree::Name(n) => {
+            used.insert(n.ident.to_string());
+        }
+        syn::UseTree::Rename(r) => {
+            used.insert(r.ident.to_string());
+        }
+        syn::UseTree::Group(g) => {
+            for item in &g.items {
+                collect_super_imports(item, used);
+            }
+        }
+        syn::UseTree::Glob(_) => {}
+    }
+}
+
+fn imports_super_glob(module: &syn::ItemMod) -> bool {
+    let Some((_, items)) = &module.content else {
+        return false;
+    };
+    items.iter().any(|item| {
+        matches!(item, syn::Item::Use(use_item) if matches!(
+            &use_item.tree,
+            syn::UseTree::Path(p) if p.ident == "super" && matches!(*p.tree, syn::UseTree::Glob(_))
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_misplaced_imports_point_at_their_group() {
+        let content = "use serde::Serialize;\nuse std::fmt;\nuse crate::config::Config;\nuse rayon::prelude::*;\n";
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        ImportOrderRule.check(&mut ctx);
+        
+        let related: Vec<(usize, usize, &str)> = ctx
+            .issues
+            .iter()
+            .map(|issue| (issue.location.line, issue.related[0].location.line, issue.related[0].message.as_str()))
+            .collect();
+        assert_eq!(related, [
+            (1, 2, "Move the import below `use std::fmt`"),
+            (3, 4, "Move the import below `use rayon::prelude::*`"),
+        ]);
+    }
+    
+    #[test]
+    fn test_imports_are_checked_per_scope() {
+        let content = r#"
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+mod inner {
+    use serde::Serialize;
+    use std::collections::HashMap;
+    
+    fn build() -> HashMap<String, fmt::Error> {
+        use std::io::Read;
+        HashMap::new()
+    }
+}
+
+mod parent {
+    use super::Path;
+    
+    fn flush(out: &mut dyn super::Write, path: &Path) {}
+}
+
+mod glob {
+    use super::*;
+    
+    fn buffer() -> PathBuf {}
+}
+"#;
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        UnusedImportRule.check(&mut ctx);
+        ImportOrderRule.check(&mut ctx);
+        
+        let messages: Vec<(usize, &str)> = ctx
+            .issues
+            .iter()
+            .map(|issue| (issue.location.line, issue.message.as_str()))
+            .collect();
+        // `fmt::Error` inside `mod inner` names a different `fmt`
+        assert_eq!(messages, [
+            (2, "Unused import: fmt"),
+            (7, "Unused import: Serialize"),
+            (11, "Unused import: Read"),
+            (7, "External imports should come after standard library imports"),
+        ]);
+    }
+}
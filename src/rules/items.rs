@@ -0,0 +1,319 @@
+use super::*;
+use syn::spanned::Spanned;
+
+/// Where a function is defined, which decides what rules expect of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FnContext {
+    /// A free function, at any depth
+    Free,
+    /// A method in an inherent `impl Type` block
+    InherentImpl,
+    /// A method implementing a trait, whose name and docs come from the trait
+    TraitImpl,
+    /// A method declared in a trait, with or without a default body
+    Trait,
+}
+
+/// A function or method reached by `file_items`.
+pub struct FnItem<'a> {
+    pub sig: &'a syn::Signature,
+    pub attrs: &'a [syn::Attribute],
+    /// The function's own visibility, or the trait's for trait methods
+    pub vis: &'a syn::Visibility,
+    /// `None` for trait methods without a default body
+    pub block: Option<&'a syn::Block>,
+    pub context: FnContext,
+    /// The implementing type or the trait, for methods
+    pub owner: Option<String>,
+    /// The whole function, attributes included
+    pub span: proc_macro2::Span,
+}
+
+impl FnItem<'_> {
+    /// The function name, qualified with its type or trait for methods.
+    pub fn name(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!("{}::{}", owner, self.sig.ident),
+            None => self.sig.ident.to_string(),
+        }
+    }
+}
+
+/// A list of items sharing a namespace, where `use` declarations apply.
+#[derive(Clone, Copy)]
+pub enum Scope<'a> {
+    File(&'a syn::File),
+    Mod(&'a syn::ItemMod),
+    Block(&'a syn::Block),
+}
+
+impl<'a> Scope<'a> {
+    /// The `use` declarations made directly in this scope.
+    pub fn uses(&self) -> Vec<&'a syn::ItemUse> {
+        let use_item = |item: &'a syn::Item| match item {
+            syn::Item::Use(use_item) => Some(use_item),
+            _ => None,
+        };
+        
+        match *self {
+            Scope::File(file) => file.items.iter().filter_map(use_item).collect(),
+            Scope::Mod(module) => module
+                .content
+                .iter()
+                .flat_map(|(_, items)| items)
+                .filter_map(use_item)
+                .collect(),
+            Scope::Block(block) => block
+                .stmts
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    syn::Stmt::Item(item) => use_item(item),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+    
+    /// Visits everything in the scope except its own `use` declarations.
+    pub fn visit_body<V: Visit<'a>>(&self, visitor: &mut V) {
+        match *self {
+            Scope::File(file) => visit_non_use_items(&file.items, visitor),
+            Scope::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    visit_non_use_items(items, visitor);
+                }
+            }
+            Scope::Block(block) => {
+                for stmt in &block.stmts {
+                    if !matches!(stmt, syn::Stmt::Item(syn::Item::Use(_))) {
+                        visitor.visit_stmt(stmt);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn visit_non_use_items<'a, V: Visit<'a>>(items: &'a [syn::Item], visitor: &mut V) {
+    for item in items {
+        if !matches!(item, syn::Item::Use(_)) {
+            visitor.visit_item(item);
+        }
+    }
+}
+
+/// Everything rules check in a file, however deeply it is nested.
+pub struct FileItems<'a> {
+    /// Every item in source order: top level, inside inline modules, and
+    /// inside function bodies and other blocks
+    pub items: Vec<&'a syn::Item>,
+    /// Free functions and the methods of `impl` and `trait` blocks
+    pub functions: Vec<FnItem<'a>>,
+    /// The file, each inline module, and each block that declares items
+    pub scopes: Vec<Scope<'a>>,
+}
+
+/// Collects the items, functions and scopes of `file` in one traversal.
+pub fn file_items(file: &syn::File) -> FileItems<'_> {
+    let mut collector = ItemCollector {
+        items: FileItems {
+            items: Vec::new(),
+            functions: Vec::new(),
+            scopes: vec![Scope::File(file)],
+        },
+        owner: None,
+    };
+    collector.visit_file(file);
+    collector.items
+}
+
+/// The name of the type an `impl` block is for, without its path or generic
+/// arguments, or "impl" for types that have no name.
+pub fn self_type_name(node: &syn::ItemImpl) -> String {
+    match node.self_ty.as_ref() {
+        syn::Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+    .unwrap_or_else(|| "impl".to_string())
+}
+
+/// The `impl` or `trait` block being visited.
+struct Owner<'a> {
+    name: String,
+    context: FnContext,
+    /// The trait's visibility, which its methods share
+    vis: Option<&'a syn::Visibility>,
+}
+
+struct ItemCollector<'a> {
+    items: FileItems<'a>,
+    owner: Option<Owner<'a>>,
+}
+
+impl<'a> ItemCollector<'a> {
+    fn with_owner(&mut self, owner: Option<Owner<'a>>, visit: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.owner, owner);
+        visit(self);
+        self.owner = outer;
+    }
+    
+    fn push_method(
+        &mut self,
+        sig: &'a syn::Signature,
+        attrs: &'a [syn::Attribute],
+        vis: &'a syn::Visibility,
+        block: Option<&'a syn::Block>,
+        span: proc_macro2::Span,
+    ) {
+        let (context, owner) = match &self.owner {
+            Some(owner) => (owner.context, Some(owner.name.clone())),
+            None => (FnContext::Free, None),
+        };
+        
+        self.items.functions.push(FnItem {
+            sig,
+            attrs,
+            vis,
+            block,
+            context,
+            owner,
+            span,
+        });
+    }
+}
+
+impl<'a> Visit<'a> for ItemCollector<'a> {
+    fn visit_item(&mut self, node: &'a syn::Item) {
+        self.items.items.push(node);
+        syn::visit::visit_item(self, node);
+    }
+    
+    fn visit_item_fn(&mut self, node: &'a syn::ItemFn) {
+        self.items.functions.push(FnItem {
+            sig: &node.sig,
+            attrs: &node.attrs,
+            vis: &node.vis,
+            block: Some(&node.block),
+            context: FnContext::Free,
+            owner: None,
+            span: node.span(),
+        });
+        // Items in the body don't belong to any enclosing impl or trait
+        self.with_owner(None, |collector| syn::visit::visit_item_fn(collector, node));
+    }
+    
+    fn visit_item_impl(&mut self, node: &'a syn::ItemImpl) {
+        let owner = Owner {
+            name: self_type_name(node),
+            context: if node.trait_.is_some() {
+                FnContext::TraitImpl
+            } else {
+                FnContext::InherentImpl
+            },
+            vis: None,
+        };
+        self.with_owner(Some(owner), |collector| syn::visit::visit_item_impl(collector, node));
+    }
+    
+    fn visit_item_trait(&mut self, node: &'a syn::ItemTrait) {
+        let owner = Owner {
+            name: node.ident.to_string(),
+            context: FnContext::Trait,
+            vis: Some(&node.vis),
+        };
+        self.with_owner(Some(owner), |collector| syn::visit::visit_item_trait(collector, node));
+    }
+    
+    fn visit_impl_item_fn(&mut self, node: &'a syn::ImplItemFn) {
+        self.push_method(&node.sig, &node.attrs, &node.vis, Some(&node.block), node.span());
+        self.with_owner(None, |collector| syn::visit::visit_impl_item_fn(collector, node));
+    }
+    
+    fn visit_trait_item_fn(&mut self, node: &'a syn::TraitItemFn) {
+        let vis = self.owner.as_ref().and_then(|owner| owner.vis).unwrap_or(&syn::Visibility::Inherited);
+        self.push_method(&node.sig, &node.attrs, vis, node.default.as_ref(), node.span());
+        self.with_owner(None, |collector| syn::visit::visit_trait_item_fn(collector, node));
+    }
+    
+    fn visit_item_mod(&mut self, node: &'a syn::ItemMod) {
+        if node.content.is_some() {
+            self.items.scopes.push(Scope::Mod(node));
+        }
+        syn::visit::visit_item_mod(self, node);
+    }
+    
+    fn visit_block(&mut self, node: &'a syn::Block) {
+        if node.stmts.iter().any(|stmt| matches!(stmt, syn::Stmt::Item(_))) {
+            self.items.scopes.push(Scope::Block(node));
+        }
+        syn::visit::visit_block(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_file_items_reach_nested_functions() {
+        let source = r#"
+fn free() {
+    fn nested_in_fn() {}
+    let closure = || {
+        fn nested_in_closure() {}
+    };
+}
+
+struct Parser;
+
+impl Parser {
+    pub fn parse(&self) {}
+}
+
+impl Default for Parser {
+    fn default() -> Self { Parser }
+}
+
+pub trait Visit {
+    fn visit(&self) {}
+    fn required(&self);
+}
+
+mod inner {
+    use std::fmt;
+
+    mod deeper {
+        fn deepest() {}
+    }
+}
+"#;
+        let file = syn::parse_file(source).unwrap();
+        let items = file_items(&file);
+        
+        let functions: Vec<(String, FnContext, bool)> = items
+            .functions
+            .iter()
+            .map(|function| (function.name(), function.context, function.block.is_some()))
+            .collect();
+        assert_eq!(functions, [
+            ("free".to_string(), FnContext::Free, true),
+            ("nested_in_fn".to_string(), FnContext::Free, true),
+            ("nested_in_closure".to_string(), FnContext::Free, true),
+            ("Parser::parse".to_string(), FnContext::InherentImpl, true),
+            ("Parser::default".to_string(), FnContext::TraitImpl, true),
+            ("Visit::visit".to_string(), FnContext::Trait, true),
+            ("Visit::required".to_string(), FnContext::Trait, false),
+            ("deepest".to_string(), FnContext::Free, true),
+        ]);
+        
+        // Trait methods share the trait's visibility
+        assert!(matches!(items.functions[5].vis, syn::Visibility::Public(_)));
+        
+        let mods = items.items.iter().filter(|item| matches!(item, syn::Item::Mod(_))).count();
+        assert_eq!(mods, 2);
+        
+        // The file, both inline modules, and the two bodies declaring functions
+        assert_eq!(items.scopes.len(), 5);
+        assert_eq!(items.scopes[3].uses().len(), 1);
+    }
+}
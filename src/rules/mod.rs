@@ -7,22 +7,34 @@ mod style;
 mod imports;
 mod unsafe_code;
 mod complexity;
+mod size;
 mod docs;
 mod unwrap_usage;
 mod todo_macros;
+mod todo_comments;
 mod must_use;
 mod anti_patterns;
+mod panics;
+mod items;
+mod test_code;
+mod comments;
 
 pub use syntax::*;
 pub use style::*;
 pub use imports::*;
 pub use unsafe_code::*;
 pub use complexity::*;
+pub use size::*;
 pub use docs::*;
 pub use unwrap_usage::*;
 pub use todo_macros::*;
+pub use todo_comments::*;
 pub use must_use::*;
 pub use anti_patterns::*;
+pub use panics::*;
+pub use items::*;
+pub use test_code::*;
+pub use comments::*;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Issue {
@@ -31,6 +43,9 @@ pub struct Issue {
     pub message: String,
     pub location: Location,
     pub fix: Option<Fix>,
+    /// Other places that explain the issue, such as the item it conflicts with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedLocation>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -41,10 +56,21 @@ pub struct Location {
     pub end_column: Option<usize>,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RelatedLocation {
+    pub location: Location,
+    pub message: String,
+    /// File the location is in when it differs from the issue's file
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Fix {
     pub description: String,
     pub replacements: Vec<Replacement>,
+    #[serde(default)]
+    pub applicability: Applicability,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -52,6 +78,19 @@ pub struct Replacement {
     pub start: usize,
     pub end: usize,
     pub text: String,
+    /// Target file when it differs from the file the issue was reported in
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+/// Whether a fix can be applied without review.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Applicability {
+    /// The fix preserves behaviour
+    #[default]
+    Safe,
+    /// The fix may change behaviour or break code outside the linted tree
+    Unsafe,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -61,11 +100,26 @@ pub enum Severity {
     Info,
 }
 
+self_cell::self_cell!(
+    /// A syntax tree together with the items collected from it.
+    struct ParsedFile {
+        owner: File,
+        
+        #[covariant]
+        dependent: FileItems,
+    }
+);
+
 pub struct RuleContext {
     pub file_path: PathBuf,
     pub content: String,
-    pub syntax_tree: File,
+    parsed: ParsedFile,
     pub issues: Vec<Issue>,
+    /// Line ranges of `#[cfg(test)]` and `#[test]` items
+    test_lines: Vec<(usize, usize)>,
+    /// Rules whose findings in test code are dropped
+    allowed_in_tests: Vec<String>,
+    comments: CommentIndex,
 }
 
 pub trait Rule: Send + Sync {
@@ -76,13 +130,47 @@ pub trait Rule: Send + Sync {
 impl RuleContext {
     pub fn new(file_path: PathBuf, content: String, syntax_tree: File) -> Self {
         Self {
+            test_lines: test_line_ranges(&syntax_tree),
+            allowed_in_tests: Vec::new(),
+            comments: CommentIndex::new(&content),
+            parsed: ParsedFile::new(syntax_tree, file_items),
             file_path,
             content,
-            syntax_tree,
             issues: Vec::new(),
         }
     }
     
+    /// Drops the findings of `rules` in test code when collecting issues.
+    pub fn allow_in_tests(mut self, rules: &[&str]) -> Self {
+        self.allowed_in_tests = rules.iter().map(|rule| rule.to_string()).collect();
+        self
+    }
+    
+    /// Whether the file is an integration test, benchmark or example.
+    pub fn is_test_file(&self) -> bool {
+        is_test_path(&self.file_path)
+    }
+    
+    /// Whether a 1-based line is test code, either because of the file's
+    /// location or because it is inside a test item.
+    pub fn is_test_code(&self, line: usize) -> bool {
+        self.is_test_file() || self.test_lines.iter().any(|&(start, end)| (start..=end).contains(&line))
+    }
+    
+    pub fn syntax_tree(&self) -> &File {
+        self.parsed.borrow_owner()
+    }
+    
+    /// The file's items, functions and scopes, collected once for all rules.
+    pub fn items(&self) -> &FileItems<'_> {
+        self.parsed.borrow_dependent()
+    }
+    
+    /// The file's comments, including the plain ones `syn` drops.
+    pub fn comments(&self) -> &CommentIndex {
+        &self.comments
+    }
+    
     pub fn report(&mut self, issue: Issue) {
         self.issues.push(issue);
     }
@@ -94,16 +182,79 @@ impl RuleContext {
             message,
             location,
             fix,
+            related: Vec::new(),
         });
     }
     
-    pub fn line_col(&self, _span: proc_macro2::Span) -> (usize, usize) {
-        // For now, return line 1, column 1 as a fallback
-        // In a real implementation, we'd need to track spans properly
-        (1, 1)
+    pub fn line_col(&self, span: proc_macro2::Span) -> (usize, usize) {
+        // Spans carry 1-based lines and 0-based character columns
+        let start = span.start();
+        (start.line.max(1), start.column + 1)
+    }
+    
+    /// Byte offset of the first character of a 1-based line.
+    pub fn line_start_offset(&self, line: usize) -> usize {
+        self.content
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(|l| l.len())
+            .sum()
+    }
+    
+    /// Consumes the context, dropping issues silenced by a suppression
+    /// comment on the same line or on its own on the line above.
+    pub fn into_issues(mut self) -> Vec<Issue> {
+        let issues = std::mem::take(&mut self.issues);
+        let comments = &self.comments;
+        let allows = |line: usize, rule: &str| {
+            comments
+                .on_line(line)
+                .any(|comment| suppressed_rules(&comment.text).any(|allowed| allowed == rule))
+        };
+        
+        issues
+            .into_iter()
+            .filter(|issue| !(self.allowed_in_tests.contains(&issue.rule) && self.is_test_code(issue.location.line)))
+            .filter(|issue| {
+                let line = issue.location.line;
+                let same_line = allows(line, &issue.rule);
+                // A comment on the line above only counts when it stands alone
+                let line_above = line > 1 && !comments.line_has_code(line - 1) && allows(line - 1, &issue.rule);
+                !(same_line || line_above)
+            })
+            .collect()
+    }
+    
+    /// Leading whitespace of a 1-based line.
+    pub fn line_indent(&self, line: usize) -> &str {
+        let text = self.content.lines().nth(line.saturating_sub(1)).unwrap_or("");
+        &text[..text.len() - text.trim_start().len()]
     }
 }
 
+/// Marker that silences rules on its own line, or on the next line when the
+/// comment stands alone, e.g. `// cargo-fl: allow(unwrap_usage, anti_patterns)`.
+pub const SUPPRESSION_MARKER: &str = "cargo-fl: allow(";
+
+/// The comment that silences `rule`.
+pub fn suppression_comment(rule: &str) -> String {
+    format!("// {}{})", SUPPRESSION_MARKER, rule)
+}
+
+/// Rules named by a suppression comment, if it is one.
+fn suppressed_rules(comment: &str) -> impl Iterator<Item = &str> {
+    let list = comment
+        .find(SUPPRESSION_MARKER)
+        .and_then(|start| {
+            let start = start + SUPPRESSION_MARKER.len();
+            let end = comment[start..].find(')')? + start;
+            Some(&comment[start..end])
+        })
+        .unwrap_or("");
+    
+    list.split(',').map(str::trim).filter(|rule| !rule.is_empty())
+}
+
 impl Severity {
     pub fn github_level(&self) -> &'static str {
         match self {
@@ -154,7 +305,7 @@ pub fn get_enabled_rules(config: &Config) -> Vec<Box<dyn Rule>> {
     
     // Style rules
     if config.rules.check_naming {
-        rules.push(Box::new(NamingConventionRule));
+        rules.push(Box::new(NamingConventionRule::new(config.naming.allow_upper_case_acronyms)));
     }
     
     if config.rules.check_line_length {
@@ -180,11 +331,23 @@ pub fn get_enabled_rules(config: &Config) -> Vec<Box<dyn Rule>> {
         rules.push(Box::new(CognitiveComplexityRule::new(
             config.complexity.max_cognitive
         )));
+        rules.push(Box::new(MaxNestingRule::new(config.complexity.max_nesting)));
+    }
+    
+    // Size rules
+    if config.rules.check_size {
+        rules.push(Box::new(FunctionLengthRule::new(config.size.max_function_lines)));
+        rules.push(Box::new(TooManyParametersRule::new(config.size.max_params)));
+        rules.push(Box::new(TooManyFieldsRule::new(config.size.max_fields)));
+        rules.push(Box::new(TooManyVariantsRule::new(config.size.max_variants)));
+        rules.push(Box::new(TooManyMethodsRule::new(config.size.max_methods)));
+        rules.push(Box::new(TooManyGenericsRule::new(config.size.max_generic_params)));
+        rules.push(Box::new(FileLengthRule::new(config.size.max_file_lines)));
     }
     
     // Documentation rules
     if config.rules.check_missing_docs {
-        rules.push(Box::new(MissingDocsRule));
+        rules.push(Box::new(MissingDocsRule::new(config.autofix.add_missing_docs)));
     }
     
     // New quality rules
@@ -196,6 +359,10 @@ pub fn get_enabled_rules(config: &Config) -> Vec<Box<dyn Rule>> {
         rules.push(Box::new(TodoMacroRule));
     }
     
+    if config.rules.check_todo_comments {
+        rules.push(Box::new(TodoCommentRule::new(&config.todo_comments)));
+    }
+    
     if config.rules.check_must_use {
         rules.push(Box::new(MustUseRule));
     }
@@ -204,5 +371,41 @@ pub fn get_enabled_rules(config: &Config) -> Vec<Box<dyn Rule>> {
         rules.push(Box::new(AntiPatternsRule));
     }
     
+    if config.rules.check_may_panic {
+        rules.push(Box::new(MayPanicRule::new(&config.may_panic)));
+    }
+    
     rules
-}
\ No newline at end of file
+}
+
+/// Rules whose `allow_in_tests` option is set, for `RuleContext::allow_in_tests`.
+pub fn rules_allowed_in_tests(config: &Config) -> Vec<&'static str> {
+    [
+        ("unwrap_usage", &config.unwrap_usage),
+        ("todo_macros", &config.todo_macros),
+        ("anti_patterns", &config.anti_patterns),
+    ]
+    .into_iter()
+    .filter(|(_, options)| options.allow_in_tests)
+    .map(|(rule, _)| rule)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_suppression_comments() {
+        let content = "fn main() {\n    // cargo-fl: allow(unwrap_usage)\n    a.unwrap();\n    b.unwrap(); /* cargo-fl: allow(anti_patterns, unwrap_usage) */\n    c.unwrap();\n    d.unwrap(\"// cargo-fl: allow(unwrap_usage)\");\n}\n";
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("main.rs".into(), content.to_string(), syntax_tree);
+        UnwrapUsageRule.check(&mut ctx);
+        assert_eq!(ctx.issues.len(), 4);
+        
+        // Markers only count in comments, not in string literals
+        let lines: Vec<usize> = ctx.into_issues().iter().map(|issue| issue.location.line).collect();
+        assert_eq!(lines, [5, 6]);
+        assert_eq!(suppression_comment("unwrap_usage"), "// cargo-fl: allow(unwrap_usage)");
+    }
+}
@@ -10,7 +10,7 @@ impl Rule for MustUseRule {
     }
 
     fn check(&self, ctx: &mut RuleContext) {
-        let syntax_tree = ctx.syntax_tree.clone();
+        let syntax_tree = ctx.syntax_tree().clone();
         let mut visitor = MustUseVisitor::new(ctx);
         visitor.visit_file(&syntax_tree);
     }
@@ -70,6 +70,7 @@ impl<'a> MustUseVisitor<'a> {
                 end_column: Some(col + 10), // Approximate
             },
             fix: None,
+            related: Vec::new(),
         });
     }
 
@@ -131,6 +132,7 @@ impl<'a> Visit<'a> for MustUseVisitor<'a> {
                                 end_column: Some(col + func_name.len()),
                             },
                             fix: None,
+                            related: Vec::new(),
                         });
                     }
                     _ => {}
@@ -162,6 +164,7 @@ impl<'a> Visit<'a> for MustUseVisitor<'a> {
                             end_column: Some(col + method_name.len()),
                         },
                         fix: None,
+                        related: Vec::new(),
                     });
                 }
                 "collect" => {
@@ -194,6 +197,7 @@ impl<'a> Visit<'a> for MustUseVisitor<'a> {
                         end_column: Some(col + method_name.len()),
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
             _ => {}
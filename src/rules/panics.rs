@@ -0,0 +1,443 @@
+use super::*;
+use crate::config::MayPanicConfig;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path};
+use syn::spanned::Spanned;
+
+pub struct MayPanicRule {
+    /// Count `assert!`, `assert_eq!` and `assert_ne!` as panics
+    asserts: bool,
+    /// Count the `debug_assert` macros, which only panic in debug builds
+    debug_asserts: bool,
+}
+
+impl MayPanicRule {
+    pub fn new(config: &MayPanicConfig) -> Self {
+        Self {
+            asserts: config.asserts,
+            debug_asserts: config.debug_asserts,
+        }
+    }
+}
+
+impl Rule for MayPanicRule {
+    fn name(&self) -> &'static str {
+        "may-panic"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        // Binaries and tests have no callers to document panics for
+        if ctx.is_test_file() || is_binary_path(&ctx.file_path) {
+            return;
+        }
+        
+        let items = ctx.items();
+        let functions = &items.functions;
+        let analysis: Vec<PanicVisitor> = functions
+            .iter()
+            .map(|function| {
+                let mut visitor = PanicVisitor::new(self, functions, function.owner.as_deref(), function.sig);
+                if let Some(block) = function.block {
+                    visitor.visit_block(block);
+                }
+                visitor
+            })
+            .collect();
+        let may_panic = propagate(&analysis);
+        
+        let mut issues_to_report = Vec::new();
+        for (index, function) in functions.iter().enumerate() {
+            // Trait implementations are documented on the trait
+            if !may_panic[index]
+                || function.context == FnContext::TraitImpl
+                || !is_pub(function.vis)
+                || has_doc_section(function.attrs, "Panics")
+            {
+                continue;
+            }
+            let (line, col) = ctx.line_col(function.sig.ident.span());
+            if ctx.is_test_code(line) {
+                continue;
+            }
+            
+            let sites = analysis[index].sites.iter().map(|(span, what)| (*span, format!("{} may panic here", what)));
+            let calls = analysis[index]
+                .calls
+                .iter()
+                .filter(|(_, callee)| may_panic[*callee])
+                .map(|(span, callee)| (*span, format!("Calls '{}', which may panic", functions[*callee].name())));
+            let related = sites
+                .chain(calls)
+                .map(|(span, message)| {
+                    let (line, column) = ctx.line_col(span);
+                    RelatedLocation {
+                        location: Location {
+                            line,
+                            column,
+                            end_line: None,
+                            end_column: None,
+                        },
+                        message,
+                        file: None,
+                    }
+                })
+                .collect::<Vec<_>>();
+            
+            let kind = if function.owner.is_some() { "method" } else { "function" };
+            let mut issue = Issue {
+                rule: self.name().to_string(),
+                severity: Severity::Warning,
+                message: format!(
+                    "Public {} '{}' may panic but its docs have no `# Panics` section",
+                    kind,
+                    function.name()
+                ),
+                location: Location {
+                    line,
+                    column: col,
+                    end_line: None,
+                    end_column: None,
+                },
+                fix: None,
+                related,
+            };
+            issue.related.sort_by_key(|related| (related.location.line, related.location.column));
+            issues_to_report.push(issue);
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+/// Whether `path` is a binary's source: `main.rs` or a file under `src/bin/`.
+fn is_binary_path(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "main.rs")
+        || path
+            .components()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .any(|pair| pair == [Component::Normal("src".as_ref()), Component::Normal("bin".as_ref())])
+}
+
+/// Which functions may panic, directly or through the functions of the same
+/// file they call.
+fn propagate(analysis: &[PanicVisitor]) -> Vec<bool> {
+    let mut may_panic: Vec<bool> = analysis.iter().map(|function| !function.sites.is_empty()).collect();
+    
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (index, function) in analysis.iter().enumerate() {
+            if !may_panic[index] && function.calls.iter().any(|(_, callee)| may_panic[*callee]) {
+                may_panic[index] = true;
+                changed = true;
+            }
+        }
+    }
+    may_panic
+}
+
+/// Finds the panicking constructs in a function body and the calls it makes
+/// to other functions in the file. Types aren't known, so this recognizes
+/// floats by their declared type and counts any other division by something
+/// other than a literal, and any indexing other than a literal range.
+struct PanicVisitor<'a> {
+    rule: &'a MayPanicRule,
+    /// Functions callable by name, and methods by type and name
+    targets: HashMap<(Option<&'a str>, String), usize>,
+    owner: Option<&'a str>,
+    /// Parameters and locals declared as floats
+    floats: HashSet<String>,
+    sites: Vec<(proc_macro2::Span, String)>,
+    calls: Vec<(proc_macro2::Span, usize)>,
+}
+
+impl<'a> PanicVisitor<'a> {
+    fn new(rule: &'a MayPanicRule, functions: &'a [FnItem<'a>], owner: Option<&'a str>, sig: &syn::Signature) -> Self {
+        let targets = functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| function.block.is_some())
+            .map(|(index, function)| ((function.owner.as_deref(), function.sig.ident.to_string()), index))
+            .collect();
+        
+        let mut visitor = Self {
+            rule,
+            targets,
+            owner,
+            floats: HashSet::new(),
+            sites: Vec::new(),
+            calls: Vec::new(),
+        };
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(arg) = input {
+                visitor.declare(&arg.pat, Some(&arg.ty), None);
+            }
+        }
+        visitor
+    }
+    
+    fn declare(&mut self, pat: &syn::Pat, ty: Option<&syn::Type>, init: Option<&syn::Expr>) {
+        let (pat, ty) = match pat {
+            syn::Pat::Type(typed) => (typed.pat.as_ref(), Some(typed.ty.as_ref())),
+            _ => (pat, ty),
+        };
+        let syn::Pat::Ident(ident) = pat else { return };
+        
+        if ty.is_some_and(is_float_type) || init.is_some_and(|init| self.is_float(init)) {
+            self.floats.insert(ident.ident.to_string());
+        } else {
+            // A shadowing binding that isn't a float
+            self.floats.remove(&ident.ident.to_string());
+        }
+    }
+    
+    fn is_float(&self, expr: &syn::Expr) -> bool {
+        match expr {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Float(_), .. }) => true,
+            syn::Expr::Paren(paren) => self.is_float(&paren.expr),
+            syn::Expr::Unary(unary) => self.is_float(&unary.expr),
+            syn::Expr::Cast(cast) => is_float_type(&cast.ty),
+            syn::Expr::Binary(binary) => self.is_float(&binary.left) || self.is_float(&binary.right),
+            syn::Expr::Path(path) => path
+                .path
+                .get_ident()
+                .is_some_and(|ident| self.floats.contains(&ident.to_string())),
+            _ => false,
+        }
+    }
+    
+    /// Whether dividing `left` by `right` might divide an integer by zero:
+    /// not when either side is a float or the divisor is a non-zero literal.
+    fn may_be_integer_division(&self, left: &syn::Expr, right: &syn::Expr) -> bool {
+        if self.is_float(left) || self.is_float(right) {
+            return false;
+        }
+        match right {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(divisor), .. }) => {
+                divisor.base10_digits().chars().all(|digit| digit == '0')
+            }
+            _ => true,
+        }
+    }
+    
+    fn call(&mut self, span: proc_macro2::Span, owner: Option<&'a str>, name: String) {
+        if let Some(&index) = self.targets.get(&(owner, name)) {
+            self.calls.push((span, index));
+        }
+    }
+}
+
+impl<'a> Visit<'a> for PanicVisitor<'a> {
+    fn visit_item(&mut self, _: &'a syn::Item) {
+        // Nested items are analyzed on their own
+    }
+    
+    fn visit_local(&mut self, node: &'a syn::Local) {
+        if let Some(init) = &node.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        self.declare(&node.pat, None, node.init.as_ref().map(|init| init.expr.as_ref()));
+    }
+    
+    fn visit_macro(&mut self, node: &'a syn::Macro) {
+        let Some(name) = node.path.segments.last().map(|segment| segment.ident.to_string()) else { return };
+        let panics = match name.as_str() {
+            "panic" | "unreachable" | "todo" | "unimplemented" => true,
+            "assert" | "assert_eq" | "assert_ne" => self.rule.asserts,
+            "debug_assert" | "debug_assert_eq" | "debug_assert_ne" => self.rule.debug_asserts,
+            _ => false,
+        };
+        if panics {
+            self.sites.push((node.span(), format!("`{}!`", name)));
+        }
+    }
+    
+    fn visit_expr_method_call(&mut self, node: &'a syn::ExprMethodCall) {
+        let name = node.method.to_string();
+        match name.as_str() {
+            "unwrap" | "expect" | "unwrap_err" | "expect_err" | "borrow_mut" => {
+                self.sites.push((node.method.span(), format!("`{}()`", name)));
+            }
+            _ => {
+                if matches!(node.receiver.as_ref(), syn::Expr::Path(path) if path.path.is_ident("self")) {
+                    self.call(node.method.span(), self.owner, name);
+                }
+            }
+        }
+        syn::visit::visit_expr_method_call(self, node);
+    }
+    
+    fn visit_expr_call(&mut self, node: &'a syn::ExprCall) {
+        if let syn::Expr::Path(path) = node.func.as_ref() {
+            let segments = &path.path.segments;
+            let name = segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default();
+            match segments.len() {
+                1 => self.call(node.func.span(), None, name),
+                2 => {
+                    let qualifier = segments[0].ident.to_string();
+                    if qualifier == "Self" {
+                        self.call(node.func.span(), self.owner, name);
+                    } else if let Some(&index) = self.targets.get(&(Some(qualifier.as_str()), name)) {
+                        self.calls.push((node.func.span(), index));
+                    }
+                }
+                _ => {}
+            }
+        }
+        syn::visit::visit_expr_call(self, node);
+    }
+    
+    fn visit_expr_index(&mut self, node: &'a syn::ExprIndex) {
+        if !is_literal_range(&node.index) {
+            self.sites.push((node.span(), "Indexing".to_string()));
+        }
+        syn::visit::visit_expr_index(self, node);
+    }
+    
+    fn visit_expr_binary(&mut self, node: &'a syn::ExprBinary) {
+        let division = matches!(
+            node.op,
+            syn::BinOp::Div(_) | syn::BinOp::Rem(_) | syn::BinOp::DivAssign(_) | syn::BinOp::RemAssign(_)
+        );
+        if division && self.may_be_integer_division(&node.left, &node.right) {
+            self.sites.push((node.op.span(), "Integer division".to_string()));
+        }
+        syn::visit::visit_expr_binary(self, node);
+    }
+}
+
+fn is_float_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Paren(paren) => is_float_type(&paren.elem),
+        syn::Type::Path(path) => path.path.is_ident("f32") || path.path.is_ident("f64"),
+        _ => false,
+    }
+}
+
+/// Whether `index` is a range whose bounds are all literals, e.g. `..2` or `1..`.
+fn is_literal_range(index: &syn::Expr) -> bool {
+    let syn::Expr::Range(range) = index else { return false };
+    [&range.start, &range.end]
+        .into_iter()
+        .flatten()
+        .all(|bound| matches!(bound.as_ref(), syn::Expr::Lit(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_may_panic() {
+        let content = r#"
+/// Parses a number.
+pub fn parse(text: &str) -> u32 {
+    text.parse().unwrap()
+}
+
+/// Parses a number.
+///
+/// # Panics
+///
+/// If `text` isn't a number.
+pub fn parse_documented(text: &str) -> u32 {
+    text.parse().unwrap()
+}
+
+/// The first value.
+pub fn first(values: &[u32]) -> u32 {
+    helper(values)
+}
+
+fn helper(values: &[u32]) -> u32 {
+    values[0]
+}
+
+pub struct Stats;
+
+impl Stats {
+    /// The mean.
+    pub fn mean(&self, total: u32, count: u32) -> f64 {
+        debug_assert!(count > 0);
+        (total / count) as f64 / 2.0
+    }
+    
+    /// Halves a value.
+    pub fn half(&self, value: u32) -> u32 {
+        self.check(value);
+        value / 2
+    }
+    
+    fn check(&self, value: u32) {
+        assert!(value % 2 == 0, "odd");
+    }
+}
+
+pub fn safe(values: &[u32]) -> Option<u32> {
+    values.first().copied()
+}
+
+/// Float math and literal slices aren't integer division or indexing.
+pub fn scaled(values: &[f32], total: f64, count: f64) -> (f64, &[f32]) {
+    let scale = count as f64 * 2.0;
+    let mut share = total / scale;
+    share %= count;
+    (share, &values[1..])
+}
+
+#[cfg(test)]
+mod tests {
+    pub fn fixture() -> u32 {
+        "1".parse().unwrap()
+    }
+}
+"#;
+        let check = |config: &MayPanicConfig, path: &str| {
+            let syntax_tree = syn::parse_file(content).unwrap();
+            let mut ctx = RuleContext::new(path.into(), content.to_string(), syntax_tree);
+            MayPanicRule::new(config).check(&mut ctx);
+            ctx.issues
+        };
+        
+        let issues = check(&MayPanicConfig::default(), "src/lib.rs");
+        let messages: Vec<(usize, &str, Vec<&str>)> = issues
+            .iter()
+            .map(|issue| {
+                let related = issue.related.iter().map(|related| related.message.as_str()).collect();
+                (issue.location.line, issue.message.as_str(), related)
+            })
+            .collect();
+        assert_eq!(messages, [
+            (3, "Public function 'parse' may panic but its docs have no `# Panics` section", vec!["`unwrap()` may panic here"]),
+            (17, "Public function 'first' may panic but its docs have no `# Panics` section", vec!["Calls 'helper', which may panic"]),
+            (
+                29,
+                "Public method 'Stats::mean' may panic but its docs have no `# Panics` section",
+                vec!["Integer division may panic here"],
+            ),
+            (35, "Public method 'Stats::half' may panic but its docs have no `# Panics` section", vec!["Calls 'Stats::check', which may panic"]),
+        ]);
+        assert_eq!(issues[1].related[0].location.line, 18);
+        
+        // Asserts can be left out, and debug asserts counted
+        let config = MayPanicConfig {
+            asserts: false,
+            debug_asserts: true,
+        };
+        let related: Vec<usize> = check(&config, "src/lib.rs")
+            .iter()
+            .flat_map(|issue| issue.related.iter().map(|related| related.location.line))
+            .collect();
+        assert_eq!(related, [4, 18, 30, 31]);
+        
+        assert!(check(&MayPanicConfig::default(), "src/main.rs").is_empty());
+        assert!(check(&MayPanicConfig::default(), "src/bin/tool.rs").is_empty());
+    }
+}
@@ -0,0 +1,455 @@
+use super::*;
+use std::ops::RangeInclusive;
+use syn::spanned::Spanned;
+
+pub struct FunctionLengthRule {
+    max_lines: usize,
+}
+
+impl FunctionLengthRule {
+    pub fn new(max_lines: usize) -> Self {
+        Self { max_lines }
+    }
+}
+
+impl Rule for FunctionLengthRule {
+    fn name(&self) -> &'static str {
+        "too-many-lines"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let mut issues_to_report = Vec::new();
+        
+        for function in &ctx.items().functions {
+            let Some(block) = function.block else { continue };
+            let start = function.sig.fn_token.span.start().line;
+            let end = block.span().end().line;
+            let length = count_sloc(ctx.comments(), start..=end);
+            
+            if length > self.max_lines {
+                let (line, col) = ctx.line_col(function.sig.ident.span());
+                issues_to_report.push(size_issue(
+                    self.name(),
+                    format!(
+                        "Function '{}' has {} lines of code (max: {})",
+                        function.name(),
+                        length,
+                        self.max_lines
+                    ),
+                    line,
+                    col,
+                ));
+            }
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+pub struct TooManyParametersRule {
+    max_params: usize,
+}
+
+impl TooManyParametersRule {
+    pub fn new(max_params: usize) -> Self {
+        Self { max_params }
+    }
+}
+
+impl Rule for TooManyParametersRule {
+    fn name(&self) -> &'static str {
+        "too-many-parameters"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let mut issues_to_report = Vec::new();
+        
+        for function in &ctx.items().functions {
+            // A trait implementation can't choose its signature
+            if function.context == FnContext::TraitImpl {
+                continue;
+            }
+            let params = function
+                .sig
+                .inputs
+                .iter()
+                .filter(|input| matches!(input, syn::FnArg::Typed(_)))
+                .count();
+            
+            if params > self.max_params {
+                let (line, col) = ctx.line_col(function.sig.ident.span());
+                issues_to_report.push(size_issue(
+                    self.name(),
+                    format!(
+                        "Function '{}' has {} parameters (max: {})",
+                        function.name(),
+                        params,
+                        self.max_params
+                    ),
+                    line,
+                    col,
+                ));
+            }
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+pub struct TooManyFieldsRule {
+    max_fields: usize,
+}
+
+impl TooManyFieldsRule {
+    pub fn new(max_fields: usize) -> Self {
+        Self { max_fields }
+    }
+}
+
+impl Rule for TooManyFieldsRule {
+    fn name(&self) -> &'static str {
+        "too-many-fields"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let mut issues_to_report = Vec::new();
+        
+        for item in &ctx.items().items {
+            let syn::Item::Struct(item) = item else { continue };
+            let fields = item.fields.len();
+            
+            if fields > self.max_fields {
+                let (line, col) = ctx.line_col(item.ident.span());
+                issues_to_report.push(size_issue(
+                    self.name(),
+                    format!("Struct '{}' has {} fields (max: {})", item.ident, fields, self.max_fields),
+                    line,
+                    col,
+                ));
+            }
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+pub struct TooManyVariantsRule {
+    max_variants: usize,
+}
+
+impl TooManyVariantsRule {
+    pub fn new(max_variants: usize) -> Self {
+        Self { max_variants }
+    }
+}
+
+impl Rule for TooManyVariantsRule {
+    fn name(&self) -> &'static str {
+        "too-many-variants"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let mut issues_to_report = Vec::new();
+        
+        for item in &ctx.items().items {
+            let syn::Item::Enum(item) = item else { continue };
+            let variants = item.variants.len();
+            
+            if variants > self.max_variants {
+                let (line, col) = ctx.line_col(item.ident.span());
+                issues_to_report.push(size_issue(
+                    self.name(),
+                    format!("Enum '{}' has {} variants (max: {})", item.ident, variants, self.max_variants),
+                    line,
+                    col,
+                ));
+            }
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+pub struct TooManyMethodsRule {
+    max_methods: usize,
+}
+
+impl TooManyMethodsRule {
+    pub fn new(max_methods: usize) -> Self {
+        Self { max_methods }
+    }
+}
+
+impl Rule for TooManyMethodsRule {
+    fn name(&self) -> &'static str {
+        "too-many-methods"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let mut issues_to_report = Vec::new();
+        
+        for item in &ctx.items().items {
+            let syn::Item::Impl(item) = item else { continue };
+            // Trait implementations have the methods the trait asks for
+            if item.trait_.is_some() {
+                continue;
+            }
+            let methods = item
+                .items
+                .iter()
+                .filter(|impl_item| matches!(impl_item, syn::ImplItem::Fn(_)))
+                .count();
+            
+            if methods > self.max_methods {
+                let (line, col) = ctx.line_col(item.impl_token.span);
+                issues_to_report.push(size_issue(
+                    self.name(),
+                    format!(
+                        "impl block for '{}' has {} methods (max: {})",
+                        self_type_name(item),
+                        methods,
+                        self.max_methods
+                    ),
+                    line,
+                    col,
+                ));
+            }
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+pub struct TooManyGenericsRule {
+    max_generics: usize,
+}
+
+impl TooManyGenericsRule {
+    pub fn new(max_generics: usize) -> Self {
+        Self { max_generics }
+    }
+}
+
+impl Rule for TooManyGenericsRule {
+    fn name(&self) -> &'static str {
+        "too-many-generic-parameters"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let items = ctx.items();
+        let mut declarations: Vec<(String, &syn::Generics, proc_macro2::Span)> = Vec::new();
+        
+        for item in &items.items {
+            let (kind, ident, generics) = match item {
+                syn::Item::Struct(item) => ("Struct", &item.ident, &item.generics),
+                syn::Item::Enum(item) => ("Enum", &item.ident, &item.generics),
+                syn::Item::Union(item) => ("Union", &item.ident, &item.generics),
+                syn::Item::Trait(item) => ("Trait", &item.ident, &item.generics),
+                syn::Item::Type(item) => ("Type alias", &item.ident, &item.generics),
+                syn::Item::Impl(item) => {
+                    let name = format!("impl block for '{}'", self_type_name(item));
+                    declarations.push((name, &item.generics, item.impl_token.span));
+                    continue;
+                }
+                _ => continue,
+            };
+            declarations.push((format!("{} '{}'", kind, ident), generics, ident.span()));
+        }
+        for function in &items.functions {
+            // A trait implementation can't choose its signature
+            if function.context != FnContext::TraitImpl {
+                let name = format!("Function '{}'", function.name());
+                declarations.push((name, &function.sig.generics, function.sig.ident.span()));
+            }
+        }
+        declarations.sort_by_key(|(_, _, span)| (span.start().line, span.start().column));
+        
+        let mut issues_to_report = Vec::new();
+        for (name, generics, span) in declarations {
+            // Lifetimes are left out: they rarely make an item harder to use
+            let count = generics
+                .params
+                .iter()
+                .filter(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+                .count();
+            
+            if count > self.max_generics {
+                let (line, col) = ctx.line_col(span);
+                issues_to_report.push(size_issue(
+                    self.name(),
+                    format!("{} has {} generic parameters (max: {})", name, count, self.max_generics),
+                    line,
+                    col,
+                ));
+            }
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+pub struct FileLengthRule {
+    max_lines: usize,
+}
+
+impl FileLengthRule {
+    pub fn new(max_lines: usize) -> Self {
+        Self { max_lines }
+    }
+}
+
+impl Rule for FileLengthRule {
+    fn name(&self) -> &'static str {
+        "file-length"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let comments = ctx.comments();
+        let code: Vec<usize> = (1..=ctx.content.lines().count())
+            .filter(|&line| comments.line_has_code(line))
+            .collect();
+        
+        // Point at the first line of code past the limit
+        if let Some(&first_over) = code.get(self.max_lines) {
+            let issue = size_issue(
+                self.name(),
+                format!("File has {} lines of code (max: {})", code.len(), self.max_lines),
+                first_over,
+                1,
+            );
+            ctx.report(issue);
+        }
+    }
+}
+
+fn size_issue(rule: &str, message: String, line: usize, column: usize) -> Issue {
+    Issue {
+        rule: rule.to_string(),
+        severity: Severity::Warning,
+        message,
+        location: Location {
+            line,
+            column,
+            end_line: None,
+            end_column: None,
+        },
+        fix: None,
+        related: Vec::new(),
+    }
+}
+
+/// How many of the 1-based `lines` aren't blank and aren't only comments.
+pub fn count_sloc(comments: &CommentIndex, lines: RangeInclusive<usize>) -> usize {
+    lines.filter(|&line| comments.line_has_code(line)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn check(rule: &dyn Rule, content: &str) -> Vec<(usize, String)> {
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        rule.check(&mut ctx);
+        ctx.issues
+            .into_iter()
+            .map(|issue| (issue.location.line, issue.message))
+            .collect()
+    }
+    
+    #[test]
+    fn test_size_rules() {
+        let content = r#"
+/// Documented
+fn long(a: u8, b: u8) {
+    // Comments and blank lines don't count
+    
+    let x = 1;
+    let y = 2;
+}
+
+struct Point<T, U, 'a> {
+    x: T,
+    y: U,
+    name: &'a str,
+}
+
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl<T, U, V> Point<T, U, '_> {
+    fn new(&self, x: T, y: U) -> Self { todo!() }
+    fn with(self, v: V) {}
+    const ORIGIN: u8 = 0;
+}
+
+impl<T, U, V> Display for Point<T, U, '_> {
+    fn fmt(&self, f: &mut Formatter, a: u8, b: u8) -> Result {}
+    fn other(&self) {}
+    fn third(&self) {}
+}
+"#;
+        assert_eq!(check(&FunctionLengthRule::new(3), content), [(3, "Function 'long' has 4 lines of code (max: 3)".to_string())]);
+        assert!(check(&FunctionLengthRule::new(4), content).is_empty());
+        
+        assert_eq!(check(&TooManyParametersRule::new(1), content), [
+            (3, "Function 'long' has 2 parameters (max: 1)".to_string()),
+            (23, "Function 'Point::new' has 2 parameters (max: 1)".to_string()),
+        ]);
+        
+        assert_eq!(check(&TooManyFieldsRule::new(2), content), [(10, "Struct 'Point' has 3 fields (max: 2)".to_string())]);
+        assert_eq!(check(&TooManyVariantsRule::new(2), content), [(16, "Enum 'Axis' has 3 variants (max: 2)".to_string())]);
+        assert_eq!(check(&TooManyMethodsRule::new(1), content), [
+            (22, "impl block for 'Point' has 2 methods (max: 1)".to_string()),
+        ]);
+        
+        assert_eq!(check(&TooManyGenericsRule::new(2), content), [
+            (22, "impl block for 'Point' has 3 generic parameters (max: 2)".to_string()),
+            (28, "impl block for 'Point' has 3 generic parameters (max: 2)".to_string()),
+        ]);
+        assert_eq!(check(&TooManyGenericsRule::new(1), content).len(), 3);
+        
+        assert_eq!(check(&FileLengthRule::new(20), content), [(29, "File has 24 lines of code (max: 20)".to_string())]);
+        assert!(check(&FileLengthRule::new(24), content).is_empty());
+    }
+    
+    #[test]
+    fn test_count_sloc() {
+        let content = r#"fn main() {
+
+    // comment
+    /* block
+       comment */ let x = 1;
+    /* only */
+    let y = 2; /* trailing
+       comment */
+    let text = "
+// not a comment
+";
+}"#;
+        let comments = CommentIndex::new(content);
+        assert_eq!(count_sloc(&comments, 1..=12), 7);
+        assert_eq!(count_sloc(&comments, 6..=8), 1);
+    }
+}
@@ -1,6 +1,20 @@
 use super::*;
+use crate::autofix::{NamingConventionFixer, SourceFile};
+use syn::ext::IdentExt;
 
-pub struct NamingConventionRule;
+/// Checks names against rustc's conventions: snake_case for functions,
+/// modules, variables, fields, lifetimes and macros, PascalCase for types,
+/// traits, variants and type parameters, and SCREAMING_SNAKE_CASE for
+/// constants and statics.
+pub struct NamingConventionRule {
+    allow_upper_case_acronyms: bool,
+}
+
+impl NamingConventionRule {
+    pub fn new(allow_upper_case_acronyms: bool) -> Self {
+        Self { allow_upper_case_acronyms }
+    }
+}
 
 impl Rule for NamingConventionRule {
     fn name(&self) -> &'static str {
@@ -9,61 +23,283 @@ impl Rule for NamingConventionRule {
     
     fn check(&self, ctx: &mut RuleContext) {
         // Collect issues first to avoid borrowing conflicts
-        let mut issues_to_report = Vec::new();
+        let files = [SourceFile {
+            path: &ctx.file_path,
+            syntax_tree: ctx.syntax_tree(),
+        }];
+        let mut visitor = NamingVisitor {
+            rule: self,
+            ctx,
+            files: &files,
+            fixer: NamingConventionFixer::new(),
+            in_trait_impl: false,
+            top_level_pattern: false,
+            issues: Vec::new(),
+        };
+        visitor.visit_file(ctx.syntax_tree());
+        
+        let mut issues_to_report = visitor.issues;
+        issues_to_report.sort_by_key(|issue| (issue.location.line, issue.location.column));
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Case {
+    Snake,
+    Pascal,
+    Upper,
+}
+
+impl Case {
+    fn label(self) -> &'static str {
+        match self {
+            Case::Snake => "snake_case",
+            Case::Pascal => "PascalCase",
+            Case::Upper => "SCREAMING_SNAKE_CASE",
+        }
+    }
+}
+
+/// Whether a rename fix can be offered. How safe it is depends on the
+/// item's visibility and on where it is referred to. Variants and constants
+/// get none: references to them in `use Enum::*` imports and in patterns
+/// aren't resolved.
+#[derive(Clone, Copy)]
+enum Rename {
+    No,
+    Yes,
+}
+
+struct NamingVisitor<'a> {
+    rule: &'a NamingConventionRule,
+    ctx: &'a RuleContext,
+    files: &'a [SourceFile<'a>],
+    fixer: NamingConventionFixer,
+    /// Methods of a trait implementation are named by the trait
+    in_trait_impl: bool,
+    /// The next identifier pattern is a whole parameter or `let` pattern,
+    /// so it must be a binding
+    top_level_pattern: bool,
+    issues: Vec<Issue>,
+}
+
+impl NamingVisitor<'_> {
+    fn check(&mut self, kind: &str, ident: &syn::Ident, case: Case, rename: Rename) {
+        let name = ident.unraw().to_string();
+        let valid = match case {
+            Case::Snake => is_snake_case(&name),
+            Case::Pascal => is_pascal_case(&name, self.rule.allow_upper_case_acronyms),
+            Case::Upper => is_upper_case(&name),
+        };
+        if valid {
+            return;
+        }
+        
+        let fix = match (rename, case) {
+            (Rename::No, _) => None,
+            (Rename::Yes, Case::Snake) => self.fixer.create_snake_case_fix(&self.ctx.file_path, ident, self.files),
+            (Rename::Yes, Case::Pascal) => self.fixer.create_pascal_case_fix(&self.ctx.file_path, ident, self.files),
+            (Rename::Yes, Case::Upper) => self.fixer.create_upper_case_fix(&self.ctx.file_path, ident, self.files),
+        };
         
-        for item in &ctx.syntax_tree.items {
+        let (line, col) = self.ctx.line_col(ident.span());
+        self.issues.push(Issue {
+            rule: self.rule.name().to_string(),
+            severity: Severity::Warning,
+            message: format!("{} '{}' should be {}", kind, name, case.label()),
+            location: Location {
+                line,
+                column: col,
+                end_line: None,
+                end_column: None,
+            },
+            fix,
+            related: Vec::new(),
+        });
+    }
+    
+    fn check_fields(&mut self, fields: &syn::Fields) {
+        for field in fields {
+            if let Some(ident) = &field.ident {
+                self.check("Field", ident, Case::Snake, Rename::No);
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for NamingVisitor<'_> {
+    fn visit_item_fn(&mut self, node: &'a syn::ItemFn) {
+        // Functions exported to other languages keep the names they are linked by
+        if !is_foreign_abi(&node.sig) && !is_exported(&node.attrs) {
+            self.check("Function", &node.sig.ident, Case::Snake, Rename::Yes);
+        }
+        
+        let outer = std::mem::replace(&mut self.in_trait_impl, false);
+        syn::visit::visit_item_fn(self, node);
+        self.in_trait_impl = outer;
+    }
+    
+    fn visit_item_impl(&mut self, node: &'a syn::ItemImpl) {
+        let outer = std::mem::replace(&mut self.in_trait_impl, node.trait_.is_some());
+        syn::visit::visit_item_impl(self, node);
+        self.in_trait_impl = outer;
+    }
+    
+    fn visit_impl_item_fn(&mut self, node: &'a syn::ImplItemFn) {
+        if !self.in_trait_impl && !is_foreign_abi(&node.sig) && !is_exported(&node.attrs) {
+            self.check("Method", &node.sig.ident, Case::Snake, Rename::Yes);
+        }
+        
+        let outer = std::mem::replace(&mut self.in_trait_impl, false);
+        syn::visit::visit_impl_item_fn(self, node);
+        self.in_trait_impl = outer;
+    }
+    
+    fn visit_impl_item_const(&mut self, node: &'a syn::ImplItemConst) {
+        if !self.in_trait_impl {
+            self.check("Constant", &node.ident, Case::Upper, Rename::No);
+        }
+        syn::visit::visit_impl_item_const(self, node);
+    }
+    
+    fn visit_impl_item_type(&mut self, node: &'a syn::ImplItemType) {
+        if !self.in_trait_impl {
+            self.check("Associated type", &node.ident, Case::Pascal, Rename::No);
+        }
+        syn::visit::visit_impl_item_type(self, node);
+    }
+    
+    fn visit_item_trait(&mut self, node: &'a syn::ItemTrait) {
+        self.check("Trait", &node.ident, Case::Pascal, Rename::Yes);
+        
+        for item in &node.items {
             match item {
-                syn::Item::Fn(func) => {
-                    let name = func.sig.ident.to_string();
-                    if !is_snake_case(&name) && !name.starts_with("test_") {
-                        let (line, col) = ctx.line_col(func.sig.ident.span());
-                        issues_to_report.push(Issue {
-                            rule: self.name().to_string(),
-                            severity: Severity::Warning,
-                            message: format!("Function '{}' should be snake_case", name),
-                            location: Location {
-                                line,
-                                column: col,
-                                end_line: None,
-                                end_column: None,
-                            },
-                            fix: Some(Fix {
-                                description: "Convert to snake_case".to_string(),
-                                replacements: vec![Replacement {
-                                    start: 0, // Would calculate actual position
-                                    end: 0,
-                                    text: to_snake_case(&name),
-                                }],
-                            }),
-                        });
-                    }
+                syn::TraitItem::Fn(method) => {
+                    self.check("Method", &method.sig.ident, Case::Snake, Rename::Yes);
                 }
-                syn::Item::Struct(s) => {
-                    let name = s.ident.to_string();
-                    if !is_pascal_case(&name) {
-                        let (line, col) = ctx.line_col(s.ident.span());
-                        issues_to_report.push(Issue {
-                            rule: self.name().to_string(),
-                            severity: Severity::Warning,
-                            message: format!("Struct '{}' should be PascalCase", name),
-                            location: Location {
-                                line,
-                                column: col,
-                                end_line: None,
-                                end_column: None,
-                            },
-                            fix: None,
-                        });
-                    }
+                syn::TraitItem::Const(constant) => {
+                    self.check("Constant", &constant.ident, Case::Upper, Rename::No);
+                }
+                syn::TraitItem::Type(ty) => {
+                    self.check("Associated type", &ty.ident, Case::Pascal, Rename::No);
                 }
                 _ => {}
             }
         }
         
-        // Report all issues
-        for issue in issues_to_report {
-            ctx.report(issue);
+        let outer = std::mem::replace(&mut self.in_trait_impl, false);
+        syn::visit::visit_item_trait(self, node);
+        self.in_trait_impl = outer;
+    }
+    
+    fn visit_item_struct(&mut self, node: &'a syn::ItemStruct) {
+        self.check("Struct", &node.ident, Case::Pascal, Rename::Yes);
+        self.check_fields(&node.fields);
+        syn::visit::visit_item_struct(self, node);
+    }
+    
+    fn visit_item_enum(&mut self, node: &'a syn::ItemEnum) {
+        self.check("Enum", &node.ident, Case::Pascal, Rename::Yes);
+        for variant in &node.variants {
+            self.check("Variant", &variant.ident, Case::Pascal, Rename::No);
+            self.check_fields(&variant.fields);
         }
+        syn::visit::visit_item_enum(self, node);
+    }
+    
+    fn visit_item_union(&mut self, node: &'a syn::ItemUnion) {
+        self.check("Union", &node.ident, Case::Pascal, Rename::Yes);
+        for field in &node.fields.named {
+            if let Some(ident) = &field.ident {
+                self.check("Field", ident, Case::Snake, Rename::No);
+            }
+        }
+        syn::visit::visit_item_union(self, node);
+    }
+    
+    fn visit_item_type(&mut self, node: &'a syn::ItemType) {
+        self.check("Type alias", &node.ident, Case::Pascal, Rename::Yes);
+        syn::visit::visit_item_type(self, node);
+    }
+    
+    fn visit_item_const(&mut self, node: &'a syn::ItemConst) {
+        self.check("Constant", &node.ident, Case::Upper, Rename::No);
+        syn::visit::visit_item_const(self, node);
+    }
+    
+    fn visit_item_static(&mut self, node: &'a syn::ItemStatic) {
+        if !is_exported(&node.attrs) {
+            self.check("Static", &node.ident, Case::Upper, Rename::Yes);
+        }
+        syn::visit::visit_item_static(self, node);
+    }
+    
+    fn visit_item_mod(&mut self, node: &'a syn::ItemMod) {
+        // Renaming a module can mean renaming its file, so there is no fix
+        self.check("Module", &node.ident, Case::Snake, Rename::No);
+        syn::visit::visit_item_mod(self, node);
+    }
+    
+    fn visit_item_macro(&mut self, node: &'a syn::ItemMacro) {
+        if let Some(ident) = node.ident.as_ref().filter(|_| node.mac.path.is_ident("macro_rules")) {
+            self.check("Macro", ident, Case::Snake, Rename::No);
+        }
+        syn::visit::visit_item_macro(self, node);
+    }
+    
+    fn visit_item_foreign_mod(&mut self, _node: &'a syn::ItemForeignMod) {
+        // Items in `extern` blocks are named by the library they come from
+    }
+    
+    fn visit_type_param(&mut self, node: &'a syn::TypeParam) {
+        self.check("Type parameter", &node.ident, Case::Pascal, Rename::No);
+        syn::visit::visit_type_param(self, node);
+    }
+    
+    fn visit_const_param(&mut self, node: &'a syn::ConstParam) {
+        self.check("Const parameter", &node.ident, Case::Upper, Rename::No);
+        syn::visit::visit_const_param(self, node);
+    }
+    
+    fn visit_lifetime_param(&mut self, node: &'a syn::LifetimeParam) {
+        self.check("Lifetime", &node.lifetime.ident, Case::Snake, Rename::No);
+        syn::visit::visit_lifetime_param(self, node);
+    }
+    
+    fn visit_fn_arg(&mut self, node: &'a syn::FnArg) {
+        self.top_level_pattern = true;
+        syn::visit::visit_fn_arg(self, node);
+    }
+    
+    fn visit_local(&mut self, node: &'a syn::Local) {
+        self.top_level_pattern = true;
+        syn::visit::visit_local(self, node);
+    }
+    
+    fn visit_pat(&mut self, node: &'a syn::Pat) {
+        if !matches!(node, syn::Pat::Ident(_) | syn::Pat::Type(_)) {
+            self.top_level_pattern = false;
+        }
+        syn::visit::visit_pat(self, node);
+    }
+    
+    fn visit_pat_ident(&mut self, node: &'a syn::PatIdent) {
+        // Elsewhere, a bare capitalized name in a pattern is usually a
+        // constant or unit variant being matched rather than a new binding
+        let is_binding = std::mem::take(&mut self.top_level_pattern)
+            || node.by_ref.is_some()
+            || node.mutability.is_some()
+            || node.subpat.is_some()
+            || !node.ident.unraw().to_string().starts_with(char::is_uppercase);
+        if is_binding {
+            self.check("Variable", &node.ident, Case::Snake, Rename::No);
+        }
+        syn::visit::visit_pat_ident(self, node);
     }
 }
 
@@ -103,6 +339,7 @@ impl Rule for LineLengthRule {
                         end_column: None,
                     },
                     fix: None,
+                    related: Vec::new(),
                 });
             }
         }
@@ -114,23 +351,192 @@ impl Rule for LineLengthRule {
     }
 }
 
-// Helper functions
+// Helper functions, following rustc's `nonstandard_style` lints
+
+/// Lowercase words separated by single underscores. Leading and trailing
+/// underscores are allowed, as in `_unused`.
 fn is_snake_case(s: &str) -> bool {
-    s.chars().all(|c| c.is_lowercase() || c.is_numeric() || c == '_')
+    let s = s.trim_matches('_');
+    !s.contains("__") && !s.chars().any(char::is_uppercase)
+}
+
+/// Capitalized words without underscores between letters; `V1_2` is fine
+/// because its parts can't be told apart otherwise. Without
+/// `allow_acronyms`, runs of capitals like `HTTPServer` are rejected.
+fn is_pascal_case(s: &str, allow_acronyms: bool) -> bool {
+    let s = s.trim_matches('_');
+    let chars: Vec<char> = s.chars().collect();
+    let has_case = |ch: char| ch.is_uppercase() || ch.is_lowercase();
+    
+    let underscore_next_to_letter = chars
+        .windows(2)
+        .any(|pair| (pair[0] == '_' && has_case(pair[1])) || (pair[1] == '_' && has_case(pair[0])));
+    // Two capitals in a row, unless the second starts a lowercase word
+    let has_acronym = (0..chars.len().saturating_sub(1)).any(|i| {
+        chars[i].is_uppercase()
+            && chars[i + 1].is_uppercase()
+            && chars.get(i + 2).is_none_or(|next| !next.is_lowercase())
+    });
+    
+    !chars.first().is_some_and(|first| first.is_lowercase())
+        && !s.contains("__")
+        && !underscore_next_to_letter
+        && (allow_acronyms || !has_acronym)
+}
+
+/// No lowercase letters, as in `MAX_RETRIES`.
+fn is_upper_case(s: &str) -> bool {
+    !s.chars().any(char::is_lowercase)
 }
 
-fn is_pascal_case(s: &str) -> bool {
-    s.chars().next().map_or(false, |c| c.is_uppercase())
-        && s.chars().all(|c| c.is_alphanumeric())
+/// Whether the function uses a non-Rust ABI, like `extern "C" fn`.
+fn is_foreign_abi(sig: &syn::Signature) -> bool {
+    sig.abi.as_ref().is_some_and(|abi| abi.name.as_ref().is_none_or(|name| name.value() != "Rust"))
 }
 
-fn to_snake_case(s: &str) -> String {
-    let mut result = String::new();
-    for (i, ch) in s.chars().enumerate() {
-        if ch.is_uppercase() && i > 0 {
-            result.push('_');
+/// Whether the item is linked under its own name, with `#[no_mangle]` or
+/// `#[export_name]`, either bare or inside `#[unsafe(...)]`.
+fn is_exported(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let path = attr.path();
+        if path.is_ident("no_mangle") || path.is_ident("export_name") {
+            return true;
+        }
+        match &attr.meta {
+            syn::Meta::List(list) if path.is_ident("unsafe") => list
+                .tokens
+                .clone()
+                .into_iter()
+                .any(|token| matches!(token, proc_macro2::TokenTree::Ident(ident) if ident == "no_mangle" || ident == "export_name")),
+            _ => false,
         }
-        result.push(ch.to_lowercase().next().unwrap());
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_naming_convention_on_nested_items() {
+        let content = r#"
+struct Parser;
+
+impl Parser {
+    fn parseInput(&self) {}
+}
+
+impl From<u8> for Parser {
+    fn from(_: u8) -> Self { Parser }
+}
+
+trait Visit {
+    fn visitNode(&self);
+}
+
+mod inner {
+    struct bad_name;
+    
+    fn helper() {
+        fn innerHelper() {}
+    }
+}
+"#;
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+        NamingConventionRule::new(true).check(&mut ctx);
+        
+        let messages: Vec<(usize, &str)> = ctx
+            .issues
+            .iter()
+            .map(|issue| (issue.location.line, issue.message.as_str()))
+            .collect();
+        assert_eq!(messages, [
+            (5, "Method 'parseInput' should be snake_case"),
+            (13, "Method 'visitNode' should be snake_case"),
+            (17, "Struct 'bad_name' should be PascalCase"),
+            (20, "Function 'innerHelper' should be snake_case"),
+        ]);
     }
-    result
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_naming_convention_coverage() {
+        let content = r#"
+const maxRetries: u32 = 3;
+static Counter: u32 = 0;
+type byte_vec = Vec<u8>;
+enum color { light_red, Blue }
+struct Point<t, const size: usize> { X: i32, y_2: i32 }
+trait shape { const sides: u32; type area_unit; }
+mod Geometry {}
+macro_rules! MakePoint { () => {} }
+
+fn test_parseX<'Input>(Value: &'Input str, _unused: u8) {
+    let myVar = 1;
+    let (first, mut Second) = (1, 2);
+    match Some(myVar) {
+        None => {}
+        Some(OTHER @ 1) => {}
+        Some(_) => {}
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn exportedFn() {}
+extern "C" fn callbackFn() {}
+#[unsafe(export_name = "exported_static")]
+static exportedStatic: u32 = 0;
+extern "C" { fn ForeignFn(); static foreignStatic: u32; }
+
+struct HTTPServer;
+struct V1_2;
+const MAX_SIZE_2: usize = 2;
+fn _private_helper() {}
+fn r#match() {}
+"#;
+        let check = |allow_acronyms: bool| {
+            let syntax_tree = syn::parse_file(content).unwrap();
+            let mut ctx = RuleContext::new("lib.rs".into(), content.to_string(), syntax_tree);
+            NamingConventionRule::new(allow_acronyms).check(&mut ctx);
+            ctx.issues
+        };
+        
+        let issues = check(true);
+        let messages: Vec<&str> = issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(messages, [
+            "Constant 'maxRetries' should be SCREAMING_SNAKE_CASE",
+            "Static 'Counter' should be SCREAMING_SNAKE_CASE",
+            "Type alias 'byte_vec' should be PascalCase",
+            "Enum 'color' should be PascalCase",
+            "Variant 'light_red' should be PascalCase",
+            "Type parameter 't' should be PascalCase",
+            "Const parameter 'size' should be SCREAMING_SNAKE_CASE",
+            "Field 'X' should be snake_case",
+            "Trait 'shape' should be PascalCase",
+            "Constant 'sides' should be SCREAMING_SNAKE_CASE",
+            "Associated type 'area_unit' should be PascalCase",
+            "Module 'Geometry' should be snake_case",
+            "Macro 'MakePoint' should be snake_case",
+            "Function 'test_parseX' should be snake_case",
+            "Lifetime 'Input' should be snake_case",
+            "Variable 'Value' should be snake_case",
+            "Variable 'myVar' should be snake_case",
+            "Variable 'Second' should be snake_case",
+            "Variable 'OTHER' should be snake_case",
+        ]);
+        
+        // Item names come with renames; constants, variants and local names are left to the developer
+        let fix_texts: Vec<Option<&str>> = issues[..6]
+            .iter()
+            .map(|issue| issue.fix.as_ref().map(|fix| fix.replacements[0].text.as_str()))
+            .collect();
+        assert_eq!(fix_texts, [None, Some("COUNTER"), Some("ByteVec"), Some("Color"), None, None]);
+        
+        // Acronyms can be required to be camel case
+        let strict = check(false);
+        let acronym = strict.iter().find(|issue| issue.message.contains("HTTPServer")).unwrap();
+        assert_eq!(acronym.message, "Struct 'HTTPServer' should be PascalCase");
+        assert_eq!(acronym.fix.as_ref().unwrap().replacements[0].text, "HttpServer");
+        assert_eq!(strict.len(), issues.len() + 1);
+    }
+}
@@ -40,6 +40,7 @@ impl Rule for UnmatchedDelimitersRule {
                                     end_column: None,
                                 },
                                 fix: None,
+                                related: Vec::new(),
                             });
                         }
                     }
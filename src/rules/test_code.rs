@@ -0,0 +1,173 @@
+use super::*;
+use std::path::{Component, Path};
+use syn::spanned::Spanned;
+
+/// Whether `path` is under a Cargo `tests/`, `benches/` or `examples/`
+/// directory. The innermost of those and `src/` decides, so a crate
+/// checked out under some `tests/` directory still has library code.
+pub fn is_test_path(path: &Path) -> bool {
+    let parent = path.parent().unwrap_or(path);
+    parent
+        .components()
+        .rev()
+        .find_map(|component| match component {
+            Component::Normal(name) => match name.to_str() {
+                Some("tests" | "benches" | "examples") => Some(true),
+                Some("src") => Some(false),
+                _ => None,
+            },
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+/// Whether an attribute marks its item as test code: `#[test]`,
+/// `#[tokio::test]`, `#[bench]`, or a `#[cfg(...)]` requiring `test`.
+pub fn is_test_attr(attr: &syn::Attribute) -> bool {
+    let path = attr.path();
+    if path.segments.last().is_some_and(|segment| segment.ident == "test" || segment.ident == "bench") {
+        return true;
+    }
+    
+    path.is_ident("cfg") && attr.parse_args::<syn::Meta>().is_ok_and(|predicate| implies_test(&predicate))
+}
+
+/// Whether a `cfg` predicate can only hold when `test` is set.
+fn implies_test(predicate: &syn::Meta) -> bool {
+    match predicate {
+        syn::Meta::Path(path) => path.is_ident("test"),
+        syn::Meta::List(list) => match cfg_operands(list) {
+            Some(operands) if list.path.is_ident("all") => operands.iter().any(implies_test),
+            Some(operands) if list.path.is_ident("any") => !operands.is_empty() && operands.iter().all(implies_test),
+            Some(operands) if list.path.is_ident("not") => operands.iter().all(holds_without_test),
+            _ => false,
+        },
+        syn::Meta::NameValue(_) => false,
+    }
+}
+
+/// Whether a `cfg` predicate is sure to hold when `test` is not set.
+fn holds_without_test(predicate: &syn::Meta) -> bool {
+    match predicate {
+        syn::Meta::List(list) => match cfg_operands(list) {
+            Some(operands) if list.path.is_ident("all") => operands.iter().all(holds_without_test),
+            Some(operands) if list.path.is_ident("any") => operands.iter().any(holds_without_test),
+            Some(operands) if list.path.is_ident("not") => operands.iter().all(implies_test),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The predicates inside `all(...)`, `any(...)` or `not(...)`.
+fn cfg_operands(list: &syn::MetaList) -> Option<Vec<syn::Meta>> {
+    let operands = list
+        .parse_args_with(syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
+        .ok()?;
+    Some(operands.into_iter().collect())
+}
+
+/// The 1-based, inclusive line ranges of the items in `file` that are test
+/// code, outermost only.
+pub fn test_line_ranges(file: &syn::File) -> Vec<(usize, usize)> {
+    let mut collector = TestCodeCollector { ranges: Vec::new() };
+    collector.visit_file(file);
+    collector.ranges
+}
+
+struct TestCodeCollector {
+    ranges: Vec<(usize, usize)>,
+}
+
+impl TestCodeCollector {
+    /// Records the item and returns true when it is test code, in which
+    /// case there is nothing inside it left to find.
+    fn record(&mut self, attrs: &[syn::Attribute], span: proc_macro2::Span) -> bool {
+        if !attrs.iter().any(is_test_attr) {
+            return false;
+        }
+        self.ranges.push((span.start().line, span.end().line));
+        true
+    }
+}
+
+impl<'a> Visit<'a> for TestCodeCollector {
+    fn visit_item_fn(&mut self, node: &'a syn::ItemFn) {
+        if !self.record(&node.attrs, node.span()) {
+            syn::visit::visit_item_fn(self, node);
+        }
+    }
+    
+    fn visit_item_mod(&mut self, node: &'a syn::ItemMod) {
+        if !self.record(&node.attrs, node.span()) {
+            syn::visit::visit_item_mod(self, node);
+        }
+    }
+    
+    fn visit_item_impl(&mut self, node: &'a syn::ItemImpl) {
+        if !self.record(&node.attrs, node.span()) {
+            syn::visit::visit_item_impl(self, node);
+        }
+    }
+    
+    fn visit_impl_item_fn(&mut self, node: &'a syn::ImplItemFn) {
+        if !self.record(&node.attrs, node.span()) {
+            syn::visit::visit_impl_item_fn(self, node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_test_code_detection() {
+        assert!(is_test_path(Path::new("tests/integration.rs")));
+        assert!(is_test_path(Path::new("/work/crate/benches/parse.rs")));
+        assert!(is_test_path(Path::new("examples/demo/main.rs")));
+        assert!(!is_test_path(Path::new("src/lib.rs")));
+        assert!(!is_test_path(Path::new("/home/me/tests/project/src/lib.rs")));
+        assert!(!is_test_path(Path::new("tests.rs")));
+        
+        let source = r#"
+fn library() {}
+
+#[cfg(test)]
+mod tests {
+    fn helper() {}
+}
+
+#[test]
+fn standalone() {}
+
+#[tokio::test]
+async fn async_test() {}
+
+#[cfg(not(test))]
+fn release_only() {}
+
+#[cfg(all(test, feature = "slow"))]
+impl Fixture {
+    fn build() {}
+}
+
+#[cfg(all(test, feature = "notify"))]
+fn notify_fixture() {}
+
+#[cfg(any(test, feature = "testing"))]
+fn shared_fixture() {}
+
+#[cfg(any(test, all(unix, test)))]
+fn unix_fixture() {}
+
+#[cfg(not(any(not(test), windows)))]
+fn non_windows_fixture() {}
+"#;
+        let file = syn::parse_file(source).unwrap();
+        assert_eq!(
+            test_line_ranges(&file),
+            [(4, 7), (9, 10), (12, 13), (18, 21), (23, 24), (29, 30), (32, 33)]
+        );
+    }
+}
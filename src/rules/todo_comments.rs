@@ -0,0 +1,226 @@
+use super::*;
+use crate::config::TodoCommentsConfig;
+
+/// A comment starting with a tag such as `TODO` or `FIXME`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TodoComment {
+    /// The tag as configured, e.g. `FIXME`
+    pub tag: String,
+    /// From `TODO(name)`, without a leading `@`
+    pub owner: Option<String>,
+    /// An issue reference such as `#123`, `PROJ-123` or an issue URL, in
+    /// the parentheses or anywhere in the text
+    pub issue: Option<String>,
+    /// The rest of the comment line after the tag
+    pub text: String,
+    /// 1-based line and column of the tag
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Finds the tagged comments in a file. A tag counts at the start of a
+/// comment line, in upper case, followed by anything but a letter or digit.
+pub fn todo_comments(comments: &CommentIndex, tags: &[String]) -> Vec<TodoComment> {
+    let mut todos = Vec::new();
+    
+    for comment in comments.iter() {
+        for (offset, text) in comment.text.lines().enumerate() {
+            // Continuation lines of block comments often start with `*`
+            let rest = text.trim_start().trim_start_matches('*').trim_start();
+            let Some(tag) = tags.iter().find(|tag| starts_with_word(rest, tag)) else { continue };
+            
+            // Later lines of a block comment start at the beginning of their line
+            let text_column = if offset == 0 {
+                comment.start.column + 2 + usize::from(comment.doc.is_some())
+            } else {
+                0
+            };
+            let prefix = text.len() - rest.len();
+            let mut todo = parse_todo(tag, &rest[tag.len()..]);
+            todo.line = comment.start.line + offset;
+            todo.column = text_column + text[..prefix].chars().count() + 1;
+            todos.push(todo);
+        }
+    }
+    
+    todos
+}
+
+fn starts_with_word(text: &str, word: &str) -> bool {
+    text.strip_prefix(word)
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+}
+
+/// Parses what follows a tag: an optional `(owner, #issue)` and the text.
+fn parse_todo(tag: &str, rest: &str) -> TodoComment {
+    let mut owner = None;
+    let mut issue = None;
+    let mut rest = rest;
+    
+    if let Some((details, after)) = rest.strip_prefix('(').and_then(|details| details.split_once(')')) {
+        for part in details.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            if is_issue_reference(part) {
+                issue.get_or_insert_with(|| part.to_string());
+            } else {
+                owner.get_or_insert_with(|| part.trim_start_matches('@').to_string());
+            }
+        }
+        rest = after;
+    }
+    
+    let text = rest.trim_start().trim_start_matches([':', '-']).trim().to_string();
+    let issue = issue.or_else(|| {
+        text.split_whitespace()
+            .map(|word| word.trim_matches(|c: char| matches!(c, '(' | ')' | '[' | ']' | ',' | '.' | ';' | ':')))
+            .find(|word| is_issue_reference(word))
+            .map(str::to_string)
+    });
+    
+    TodoComment {
+        tag: tag.to_string(),
+        owner,
+        issue,
+        text,
+        line: 0,
+        column: 0,
+    }
+}
+
+/// `#123`, `PROJ-123`, or a URL to an issue or pull request.
+fn is_issue_reference(text: &str) -> bool {
+    let is_number = |digits: &str| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+    
+    if let Some(number) = text.strip_prefix('#') {
+        return is_number(number);
+    }
+    if text.starts_with("http://") || text.starts_with("https://") {
+        return text.contains("/issues/") || text.contains("/pull/");
+    }
+    text.split_once('-').is_some_and(|(project, number)| {
+        project.starts_with(|c: char| c.is_ascii_uppercase())
+            && project.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            && is_number(number)
+    })
+}
+
+pub struct TodoCommentRule {
+    tags: Vec<String>,
+    require_owner: bool,
+    require_issue: bool,
+}
+
+impl TodoCommentRule {
+    pub fn new(config: &TodoCommentsConfig) -> Self {
+        Self {
+            tags: config.tags.clone(),
+            require_owner: config.require_owner,
+            require_issue: config.require_issue,
+        }
+    }
+}
+
+impl Rule for TodoCommentRule {
+    fn name(&self) -> &'static str {
+        "todo-comment"
+    }
+    
+    fn check(&self, ctx: &mut RuleContext) {
+        let mut issues_to_report = Vec::new();
+        
+        for todo in todo_comments(ctx.comments(), &self.tags) {
+            let mut missing = Vec::new();
+            if self.require_owner && todo.owner.is_none() {
+                missing.push(format!("an owner, as in `{}(name)`", todo.tag));
+            }
+            if self.require_issue && todo.issue.is_none() {
+                missing.push("an issue reference such as `#123` or `PROJ-123`".to_string());
+            }
+            
+            let (severity, message) = if !missing.is_empty() {
+                (Severity::Warning, format!("{} comment needs {}", todo.tag, missing.join(" and ")))
+            } else if todo.text.is_empty() {
+                (Severity::Info, format!("{} comment", todo.tag))
+            } else {
+                (Severity::Info, format!("{} comment: {}", todo.tag, todo.text))
+            };
+            
+            issues_to_report.push(Issue {
+                rule: self.name().to_string(),
+                severity,
+                message,
+                location: Location {
+                    line: todo.line,
+                    column: todo.column,
+                    end_line: None,
+                    end_column: None,
+                },
+                fix: None,
+                related: Vec::new(),
+            });
+        }
+        
+        // Report all issues
+        for issue in issues_to_report {
+            ctx.report(issue);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_todo_comments() {
+        let content = r#"
+// TODO(alice): handle errors, see #123
+fn main() {
+    let todo = "// TODO: not a comment";
+    call(); // FIXME - retry on failure (PROJ-42)
+    /* Notes
+     * HACK(@bob, https://github.com/org/repo/issues/7) */
+    // TODOS aren't tags, and neither is a todo in lower case
+    // XXX
+}
+"#;
+        let tags: Vec<String> = ["TODO", "FIXME", "XXX", "HACK"].map(String::from).to_vec();
+        let todos = todo_comments(&CommentIndex::new(content), &tags);
+        let summary: Vec<_> = todos
+            .iter()
+            .map(|todo| {
+                (todo.line, todo.column, todo.tag.as_str(), todo.owner.as_deref(), todo.issue.as_deref(), todo.text.as_str())
+            })
+            .collect();
+        assert_eq!(summary, [
+            (2, 4, "TODO", Some("alice"), Some("#123"), "handle errors, see #123"),
+            (5, 16, "FIXME", None, Some("PROJ-42"), "retry on failure (PROJ-42)"),
+            (7, 8, "HACK", Some("bob"), Some("https://github.com/org/repo/issues/7"), ""),
+            (9, 8, "XXX", None, None, ""),
+        ]);
+        
+        let config = TodoCommentsConfig {
+            require_owner: true,
+            require_issue: true,
+            ..TodoCommentsConfig::default()
+        };
+        let syntax_tree = syn::parse_file(content).unwrap();
+        let mut ctx = RuleContext::new("main.rs".into(), content.to_string(), syntax_tree);
+        TodoCommentRule::new(&config).check(&mut ctx);
+        
+        let messages: Vec<(usize, Severity, &str)> = ctx
+            .issues
+            .iter()
+            .map(|issue| (issue.location.line, issue.severity, issue.message.as_str()))
+            .collect();
+        assert_eq!(messages, [
+            (2, Severity::Info, "TODO comment: handle errors, see #123"),
+            (5, Severity::Warning, "FIXME comment needs an owner, as in `FIXME(name)`"),
+            (7, Severity::Info, "HACK comment"),
+            (
+                9,
+                Severity::Warning,
+                "XXX comment needs an owner, as in `XXX(name)` and an issue reference such as `#123` or `PROJ-123`",
+            ),
+        ]);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::rules::{Issue, Location, Rule, RuleContext, Severity, Fix, Replacement};
+use crate::rules::{Applicability, Issue, Location, Rule, RuleContext, Severity, Fix, Replacement};
 use syn::visit::Visit;
+use std::ops::Range;
 use syn::{Expr, ExprCall, ExprMethodCall};
 
 pub struct UnwrapUsageRule;
@@ -10,7 +11,7 @@ impl Rule for UnwrapUsageRule {
     }
 
     fn check(&self, ctx: &mut RuleContext) {
-        let syntax_tree = ctx.syntax_tree.clone();
+        let syntax_tree = ctx.syntax_tree().clone();
         let mut visitor = UnwrapVisitor::new(ctx);
         visitor.visit_file(&syntax_tree);
     }
@@ -25,7 +26,7 @@ impl<'a> UnwrapVisitor<'a> {
         Self { ctx }
     }
 
-    fn report_unwrap(&mut self, method_name: &str, line: usize, col: usize) {
+    fn report_unwrap(&mut self, method_name: &str, line: usize, col: usize, call_range: Option<Range<usize>>) {
         let suggestion = match method_name {
             "unwrap" => "Consider using `match`, `if let`, or `expect()` with a descriptive message",
             "unwrap_or_default" => "This is generally safe, but consider explicit handling",
@@ -49,18 +50,21 @@ impl<'a> UnwrapVisitor<'a> {
                 end_line: Some(line),
                 end_column: Some(col + method_name.len()),
             },
-            fix: if method_name == "unwrap" {
-                Some(Fix {
-                    description: format!("Replace with expect() and descriptive message"),
+            fix: match call_range {
+                // The message is a placeholder the developer still has to write
+                Some(range) if method_name == "unwrap" => Some(Fix {
+                    description: "Replace with expect() and descriptive message".to_string(),
                     replacements: vec![Replacement {
-                        start: 0, // This would need proper span calculation
-                        end: 0,
+                        start: range.start,
+                        end: range.end,
                         text: "expect(\"TODO: Add descriptive error message\")".to_string(),
+                        file: None,
                     }],
-                })
-            } else {
-                None
+                    applicability: Applicability::Unsafe,
+                }),
+                _ => None,
             },
+            related: Vec::new(),
         });
     }
 }
@@ -72,7 +76,10 @@ impl<'a> Visit<'a> for UnwrapVisitor<'a> {
         match method_name.as_str() {
             "unwrap" | "unwrap_or_default" | "unwrap_unchecked" | "expect" => {
                 let (line, col) = self.ctx.line_col(method_call.method.span());
-                self.report_unwrap(&method_name, line, col);
+                // From the method name through the closing parenthesis
+                let call_range = method_call.method.span().byte_range().start
+                    ..method_call.paren_token.span.close().byte_range().end;
+                self.report_unwrap(&method_name, line, col, Some(call_range));
             }
             _ => {}
         }
@@ -88,7 +95,7 @@ impl<'a> Visit<'a> for UnwrapVisitor<'a> {
                 let func_name = last_segment.ident.to_string();
                 if func_name.contains("unwrap") {
                     let (line, col) = self.ctx.line_col(last_segment.ident.span());
-                    self.report_unwrap(&func_name, line, col);
+                    self.report_unwrap(&func_name, line, col, None);
                 }
             }
         }
@@ -96,4 +103,41 @@ impl<'a> Visit<'a> for UnwrapVisitor<'a> {
         // Continue visiting
         syn::visit::visit_expr_call(self, call);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_unwrap_allowed_in_tests() {
+        let content = r#"
+fn load() -> u32 {
+    "1".parse().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    fn fixture() -> u32 { "2".parse().unwrap() }
+
+    #[test]
+    fn loads() { assert_eq!(load(), fixture()); }
+}
+
+#[test]
+fn standalone() { "3".parse::<u32>().unwrap(); }
+"#;
+        let check = |path: &str, allow_in_tests: bool| {
+            let syntax_tree = syn::parse_file(content).unwrap();
+            let allowed: &[&str] = if allow_in_tests { &["unwrap_usage"] } else { &[] };
+            let mut ctx = RuleContext::new(path.into(), content.to_string(), syntax_tree).allow_in_tests(allowed);
+            UnwrapUsageRule.check(&mut ctx);
+            ctx.into_issues().iter().map(|issue| issue.location.line).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(check("src/lib.rs", true), [3]);
+        assert_eq!(check("src/lib.rs", false), [3, 8, 15]);
+        assert!(check("tests/load.rs", true).is_empty());
+        assert_eq!(check("tests/load.rs", false).len(), 3);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::ast_cache::read_rust_file;
+use crate::rules::{todo_comments, CommentIndex, TodoComment};
+use crate::walker::RustFileWalker;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Owners are listed by name, with unowned comments last.
+const UNASSIGNED: &str = "unassigned";
+
+/// The comments for one tag, by owner and then by file.
+type OwnerGroups<'a> = Vec<(&'a str, Vec<(&'a PathBuf, Vec<&'a TodoComment>)>)>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoEntry {
+    pub file: PathBuf,
+    #[serde(flatten)]
+    pub todo: TodoComment,
+}
+
+/// The tagged comments of a set of files, for `cargo-fl todos`.
+#[derive(Debug, Default, Serialize)]
+pub struct TodoReport {
+    /// Tags in the order they are listed
+    #[serde(skip)]
+    pub tags: Vec<String>,
+    pub todos: Vec<TodoEntry>,
+}
+
+impl TodoReport {
+    /// Finds the comments tagged with one of `tags` in every Rust file
+    /// `walker` finds under `paths`. Files that can't be read are skipped.
+    pub fn collect(paths: &[PathBuf], walker: &RustFileWalker, tags: &[String]) -> Self {
+        let mut files: Vec<PathBuf> = paths.iter().flat_map(|path| walker.walk(path)).collect();
+        files.sort();
+        files.dedup();
+
+        Self::from_sources(
+            files.into_iter().filter_map(|path| {
+                let content = read_rust_file(&path).ok()?;
+                Some((path, content))
+            }),
+            tags,
+        )
+    }
+
+    /// Finds the tagged comments in the given sources, keyed by the path
+    /// they are reported as.
+    pub fn from_sources(sources: impl IntoIterator<Item = (PathBuf, String)>, tags: &[String]) -> Self {
+        let mut todos = Vec::new();
+        for (path, content) in sources {
+            todos.extend(
+                todo_comments(&CommentIndex::new(&content), tags)
+                    .into_iter()
+                    .map(|todo| TodoEntry { file: path.clone(), todo }),
+            );
+        }
+        todos.sort_by(|a, b| (&a.file, a.todo.line).cmp(&(&b.file, b.todo.line)));
+
+        Self {
+            tags: tags.to_vec(),
+            todos,
+        }
+    }
+
+    /// Adds the comments in files this report doesn't have yet, and the tags
+    /// it doesn't list, for paths collected separately because different
+    /// configs apply to them.
+    pub fn merge(&mut self, other: TodoReport) {
+        let known: HashSet<PathBuf> = self.todos.iter().map(|entry| entry.file.clone()).collect();
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self.todos.extend(other.todos.into_iter().filter(|entry| !known.contains(&entry.file)));
+        self.todos.sort_by(|a, b| (&a.file, a.todo.line).cmp(&(&b.file, b.todo.line)));
+    }
+
+    /// The comments grouped by tag, then owner, then file, with tags in
+    /// configured order and unowned comments after the owned ones.
+    pub fn grouped(&self) -> Vec<(&str, OwnerGroups<'_>)> {
+        self.tags
+            .iter()
+            .filter_map(|tag| {
+                let mut owners: BTreeMap<(bool, &str), BTreeMap<&PathBuf, Vec<&TodoComment>>> = BTreeMap::new();
+                for entry in self.todos.iter().filter(|entry| &entry.todo.tag == tag) {
+                    let owner = entry.todo.owner.as_deref();
+                    owners
+                        .entry((owner.is_none(), owner.unwrap_or(UNASSIGNED)))
+                        .or_default()
+                        .entry(&entry.file)
+                        .or_default()
+                        .push(&entry.todo);
+                }
+                if owners.is_empty() {
+                    return None;
+                }
+
+                let owners = owners
+                    .into_iter()
+                    .map(|((_, owner), files)| (owner, files.into_iter().collect()))
+                    .collect();
+                Some((tag.as_str(), owners))
+            })
+            .collect()
+    }
+
+    /// An outline of `grouped`, with each comment's position and text.
+    pub fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        let count = |files: &[(&PathBuf, Vec<&TodoComment>)]| files.iter().map(|(_, todos)| todos.len()).sum::<usize>();
+
+        for (tag, owners) in self.grouped() {
+            let total: usize = owners.iter().map(|(_, files)| count(files)).sum();
+            writeln!(out, "{} ({})", tag, total)?;
+
+            for (owner, files) in owners {
+                writeln!(out, "  {} ({})", owner, count(&files))?;
+                for (file, todos) in files {
+                    writeln!(out, "    {}", file.display())?;
+                    for todo in todos {
+                        let mut summary = todo.text.clone();
+                        // Issues in the text are already shown with it
+                        if let Some(issue) = todo.issue.as_ref().filter(|issue| !todo.text.contains(issue.as_str())) {
+                            summary = format!("{} [{}]", summary, issue).trim_start().to_string();
+                        }
+                        let line = format!("      {:<8} {}", format!("{}:{}", todo.line, todo.column), summary);
+                        writeln!(out, "{}", line.trim_end())?;
+                    }
+                }
+            }
+        }
+
+        let files = self.todos.iter().map(|entry| &entry.file).collect::<HashSet<_>>().len();
+        writeln!(out, "{} comments in {} files", self.todos.len(), files)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.todos).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo_report() {
+        let tags: Vec<String> = ["TODO", "FIXME"].map(String::from).to_vec();
+        let report = TodoReport::from_sources(
+            [
+                (
+                    PathBuf::from("src/parse.rs"),
+                    "// TODO: handle tabs\nfn parse() {} // FIXME(bob): slow, PROJ-9\n".to_string(),
+                ),
+                (
+                    PathBuf::from("src/lib.rs"),
+                    "// TODO(alice): split\n/* TODO(bob, #12) */\n// TODO: docs\n".to_string(),
+                ),
+            ],
+            &tags,
+        );
+
+        let mut text = Vec::new();
+        report.write_text(&mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "TODO (4)
+  alice (1)
+    src/lib.rs
+      1:4      split
+  bob (1)
+    src/lib.rs
+      2:4      [#12]
+  unassigned (2)
+    src/lib.rs
+      3:4      docs
+    src/parse.rs
+      1:4      handle tabs
+FIXME (1)
+  bob (1)
+    src/parse.rs
+      2:18     slow, PROJ-9
+5 comments in 2 files
+"
+        );
+
+        let json = report.to_json();
+        assert_eq!(json[0]["file"], "src/lib.rs");
+        assert_eq!(json[0]["owner"], "alice");
+        assert_eq!(json[1]["issue"], "#12");
+    }
+
+    #[test]
+    fn test_merged_reports_keep_each_configs_tags() {
+        let mut report = TodoReport::from_sources(
+            [(PathBuf::from("b/lib.rs"), "// TODO: one
+// HACK: two
+".to_string())],
+            &["TODO".to_string()],
+        );
+        report.merge(TodoReport::from_sources(
+            [
+                (PathBuf::from("a/lib.rs"), "// HACK: three
+".to_string()),
+                (PathBuf::from("b/lib.rs"), "// TODO: one
+// HACK: two
+".to_string()),
+            ],
+            &["HACK".to_string(), "TODO".to_string()],
+        ));
+
+        assert_eq!(report.tags, ["TODO", "HACK"]);
+        let todos: Vec<(&str, &str)> = report
+            .todos
+            .iter()
+            .map(|entry| (entry.file.to_str().unwrap(), entry.todo.tag.as_str()))
+            .collect();
+        assert_eq!(todos, [("a/lib.rs", "HACK"), ("b/lib.rs", "TODO")]);
+    }
+}
@@ -1,26 +1,45 @@
+use crate::config::{Config, PathMatcher};
+use ignore::gitignore::Gitignore;
 use ignore::WalkBuilder;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 pub struct RustFileWalker {
-    builder: WalkBuilder,
+    ignore_files: bool,
+    matcher: Option<PathMatcher>,
 }
 
 impl RustFileWalker {
     pub fn new() -> Self {
-        let mut builder = WalkBuilder::new(".");
-        builder
-            .standard_filters(true)
-            .add_custom_ignore_filename(".flignore");
-            
-        Self { builder }
+        Self {
+            ignore_files: true,
+            matcher: None,
+        }
+    }
+    
+    /// A walker that also applies the `ignore` and `include` globs of `config`.
+    pub fn with_config(config: &Config) -> Self {
+        Self {
+            ignore_files: true,
+            matcher: Some(config.path_matcher()),
+        }
+    }
+    
+    /// Whether `.gitignore`, `.ignore` and `.flignore` files are respected.
+    pub fn ignore_files(mut self, enabled: bool) -> Self {
+        self.ignore_files = enabled;
+        self
     }
     
     pub fn walk(&self, path: &Path) -> impl Iterator<Item = PathBuf> {
         let mut builder = WalkBuilder::new(path);
         builder
-            .standard_filters(true)
-            .add_custom_ignore_filename(".flignore");
-            
+            .standard_filters(self.ignore_files)
+            .hidden(true);
+        if self.ignore_files {
+            builder.add_custom_ignore_filename(".flignore");
+        }
+        
+        let matcher = self.matcher.clone();
         builder.build()
             .filter_map(|entry| entry.ok())
             .filter(|entry| {
@@ -28,5 +47,127 @@ impl RustFileWalker {
                     .map_or(false, |ext| ext == "rs")
             })
             .map(|entry| entry.path().to_path_buf())
+            .filter(move |path| matcher.as_ref().is_none_or(|matcher| !matcher.is_excluded(path)))
+    }
+    
+    /// Whether `path` would be skipped by `walk` because of the config globs
+    /// or a `.gitignore`, `.ignore` or `.flignore` in one of its directories. The file doesn't
+    /// need to exist, so this works for sources read from stdin.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        if self.matcher.as_ref().is_some_and(|matcher| matcher.is_excluded(path)) {
+            return true;
+        }
+        if !self.ignore_files {
+            return false;
+        }
+        
+        let Some(path) = absolute_path(path) else { return false };
+        let in_git_repo = path.ancestors().skip(1).any(|dir| dir.join(".git").exists());
+        
+        // The closest directory decides, and within a directory `.flignore`
+        // overrides `.ignore`, which overrides `.gitignore`
+        for dir in path.ancestors().skip(1) {
+            for name in [".flignore", ".ignore", ".gitignore"] {
+                if name == ".gitignore" && !in_git_repo {
+                    continue;
+                }
+                let (matcher, _) = Gitignore::new(dir.join(name));
+                let matched = matcher.matched_path_or_any_parents(&path, false);
+                if matched.is_ignore() {
+                    return true;
+                }
+                if matched.is_whitelist() {
+                    return false;
+                }
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        
+        false
+    }
+}
+
+/// `path` made absolute through its canonical parent directory.
+pub(crate) fn absolute_path(path: &Path) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    match (path.parent().and_then(|dir| dir.canonicalize().ok()), path.file_name()) {
+        (Some(dir), Some(name)) => Some(dir.join(name)),
+        _ => Some(path),
+    }
+}
+
+/// The source root of the crate `path` belongs to and the file's module
+/// path within it, from where the file sits under `src/`: `src/rules/style.rs`
+/// is `rules::style` in the crate rooted at `src`, which `lib.rs` and
+/// `main.rs` share. Each file or directory under `src/bin/` is a crate of
+/// its own, as is any file outside `src/`, like an integration test.
+pub fn crate_module(path: &Path) -> (PathBuf, Vec<String>) {
+    let parts: Vec<Component> = path.components().filter(|component| *component != Component::CurDir).collect();
+    let Some(src) = parts.iter().rposition(|component| component.as_os_str() == "src") else {
+        return (parts.iter().collect(), Vec::new());
+    };
+    
+    let in_bin = parts.get(src + 1).is_some_and(|component| component.as_os_str() == "bin") && parts.len() > src + 2;
+    let root_len = if in_bin { src + 3 } else { src + 1 };
+    let mut segments: Vec<String> = parts[root_len..]
+        .iter()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if let Some(last) = segments.last_mut() {
+        if let Some(stem) = last.strip_suffix(".rs") {
+            *last = stem.to_string();
+        }
+    }
+    
+    let is_root = match segments.as_slice() {
+        [name] if in_bin => name == "main",
+        [name] => name == "lib" || name == "main",
+        _ => false,
+    };
+    if is_root || segments.last().is_some_and(|last| last == "mod") {
+        segments.pop();
+    }
+    
+    (parts[..root_len].iter().collect(), segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+    
+    #[test]
+    fn test_is_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("src/.flignore"), "generated/*.rs\n!generated/keep.rs\n").unwrap();
+        
+        let walker = RustFileWalker::new();
+        assert!(walker.is_ignored(&root.join("target/debug/build.rs")));
+        assert!(walker.is_ignored(&root.join("src/generated/bindings.rs")));
+        assert!(!walker.is_ignored(&root.join("src/generated/keep.rs")));
+        assert!(!walker.is_ignored(&root.join("src/lib.rs")));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_crate_module() {
+        let module = |path: &str| {
+            let (root, segments) = crate_module(Path::new(path));
+            (root.to_string_lossy().into_owned(), segments.join("::"))
+        };
+        assert_eq!(module("src/lib.rs"), ("src".to_string(), String::new()));
+        assert_eq!(module("./src/main.rs"), ("src".to_string(), String::new()));
+        assert_eq!(module("/work/app/src/rules/mod.rs"), ("/work/app/src".to_string(), "rules".to_string()));
+        assert_eq!(module("src/rules/style.rs"), ("src".to_string(), "rules::style".to_string()));
+        assert_eq!(module("src/bin/tool.rs"), ("src/bin/tool.rs".to_string(), String::new()));
+        assert_eq!(module("src/bin/tool/main.rs"), ("src/bin/tool".to_string(), String::new()));
+        assert_eq!(module("src/bin/tool/args.rs"), ("src/bin/tool".to_string(), "args".to_string()));
+        assert_eq!(module("tests/cli.rs"), ("tests/cli.rs".to_string(), String::new()));
+    }
+}
@@ -0,0 +1,439 @@
+use crate::config::Config;
+use crate::incremental::IncrementalAnalyzer;
+use crate::rules::{Issue, Severity};
+use crate::walker::RustFileWalker;
+use ahash::AHashMap;
+use colored::*;
+use notify::{RecursiveMode, Watcher as _};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Clears the terminal and moves the cursor home.
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[H";
+
+/// Modification times of every Rust file under a root, as seen by
+/// `walker`, so `.gitignore`, `.flignore` and the config globs apply.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileSnapshot {
+    files: AHashMap<PathBuf, (SystemTime, u64)>,
+}
+
+/// Files that differ between two snapshots.
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes {
+    /// Files that are new or whose contents may have changed
+    pub modified: Vec<PathBuf>,
+    /// Files that no longer exist or are now ignored
+    pub removed: Vec<PathBuf>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl FileSnapshot {
+    pub fn scan(walker: &RustFileWalker, root: &Path) -> Self {
+        let files = walker
+            .walk(root)
+            .filter_map(|path| {
+                let metadata = std::fs::metadata(&path).ok()?;
+                Some((path, (metadata.modified().ok()?, metadata.len())))
+            })
+            .collect();
+
+        Self { files }
+    }
+
+    /// This snapshot with only `paths` looked at again, for the files a file
+    /// watcher reported as created, changed or removed. A removed directory
+    /// takes its files with it; a directory that exists needs a full scan.
+    pub fn rescan(&self, walker: &RustFileWalker, paths: &[PathBuf]) -> Self {
+        let mut files = self.files.clone();
+
+        for path in paths {
+            files.retain(|file, _| !file.starts_with(path));
+
+            let is_rust = path.extension().is_some_and(|ext| ext == "rs");
+            if !is_rust || walker.is_ignored(path) {
+                continue;
+            }
+            if let Ok(metadata) = std::fs::metadata(path) {
+                if let Ok(modified) = metadata.modified() {
+                    files.insert(path.clone(), (modified, metadata.len()));
+                }
+            }
+        }
+
+        Self { files }
+    }
+
+    /// What changed going from `earlier` to this snapshot, sorted by path.
+    pub fn changes_since(&self, earlier: &FileSnapshot) -> Changes {
+        let mut modified: Vec<PathBuf> = self.files
+            .iter()
+            .filter(|(path, stamp)| earlier.files.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut removed: Vec<PathBuf> = earlier.files
+            .keys()
+            .filter(|path| !self.files.contains_key(*path))
+            .cloned()
+            .collect();
+
+        modified.sort();
+        removed.sort();
+        Changes { modified, removed }
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.files.keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+}
+
+/// Keeps the findings for a tree up to date by re-linting only the files
+/// that changed, as reported by the filesystem or found by polling.
+pub struct Watcher {
+    root: PathBuf,
+    walker: RustFileWalker,
+    analyzer: IncrementalAnalyzer,
+    snapshot: FileSnapshot,
+    issues: BTreeMap<PathBuf, Vec<Issue>>,
+    last_duration: Duration,
+    last_changed: usize,
+    /// Whether changes are found by rescanning the tree every `interval`
+    polling: Option<Duration>,
+}
+
+impl Watcher {
+    pub fn new(root: PathBuf, config: Config) -> Self {
+        Self {
+            root,
+            walker: RustFileWalker::with_config(&config),
+            analyzer: IncrementalAnalyzer::new(config),
+            snapshot: FileSnapshot::default(),
+            issues: BTreeMap::new(),
+            last_duration: Duration::ZERO,
+            last_changed: 0,
+            polling: None,
+        }
+    }
+
+    /// A watcher for `root` with the `.fl.toml` that applies to it, which may
+    /// be in a parent directory.
+    pub fn for_path(root: PathBuf) -> Self {
+        let config = Config::load_for(&root);
+        Self::new(root, config)
+    }
+
+    /// Rescans the tree and re-lints whatever changed since the last call.
+    /// The first call lints every file.
+    pub fn poll(&mut self) -> Changes {
+        let snapshot = FileSnapshot::scan(&self.walker, &self.root);
+        self.update(snapshot)
+    }
+
+    /// Re-lints whatever changed among `paths`, without looking at the rest
+    /// of the tree.
+    pub fn refresh(&mut self, paths: &[PathBuf]) -> Changes {
+        if paths.iter().any(|path| path.is_dir()) {
+            return self.poll();
+        }
+        let snapshot = self.snapshot.rescan(&self.walker, paths);
+        self.update(snapshot)
+    }
+
+    /// Applies a batch of file watcher events, falling back to a full rescan
+    /// when the watcher lost track of what happened.
+    fn handle_events(&mut self, events: Vec<notify::Result<notify::Event>>, root: &Path) -> Changes {
+        let mut paths = Vec::new();
+        for event in events {
+            let Ok(event) = event else { return self.poll() };
+            if event.need_rescan() {
+                return self.poll();
+            }
+            if event.kind.is_access() {
+                continue;
+            }
+            // Events name absolute paths, while the snapshot uses the root as given
+            paths.extend(event.paths.iter().map(|path| match path.strip_prefix(root) {
+                Ok(relative) => self.root.join(relative),
+                Err(_) => path.clone(),
+            }));
+        }
+
+        paths.sort();
+        paths.dedup();
+        self.refresh(&paths)
+    }
+
+    fn update(&mut self, snapshot: FileSnapshot) -> Changes {
+        let changes = snapshot.changes_since(&self.snapshot);
+        self.snapshot = snapshot;
+
+        if changes.is_empty() {
+            return changes;
+        }
+
+        let start = Instant::now();
+
+        for path in &changes.removed {
+            self.analyzer.invalidate_file(path);
+            self.issues.remove(path);
+        }
+        for path in &changes.modified {
+            self.analyzer.invalidate_file(path);
+        }
+
+        let results = self.analyzer.analyze_files(changes.modified.clone());
+        for path in &changes.modified {
+            // Files that no longer parse keep no findings until they do again
+            self.issues.remove(path);
+        }
+        for (path, issues) in results.all_issues() {
+            if !issues.is_empty() {
+                self.issues.insert(path, issues);
+            }
+        }
+
+        if let Err(e) = self.analyzer.save_cache() {
+            eprintln!("Warning: Failed to save cache: {}", e);
+        }
+
+        self.last_duration = start.elapsed();
+        self.last_changed = changes.modified.len() + changes.removed.len();
+        changes
+    }
+
+    pub fn issues(&self) -> &BTreeMap<PathBuf, Vec<Issue>> {
+        &self.issues
+    }
+
+    /// A compact report: one line per finding, then the totals.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let total: usize = self.issues.values().map(Vec::len).sum();
+
+        for (file, issues) in &self.issues {
+            for issue in issues {
+                let severity = match issue.severity {
+                    Severity::Error => "error".red().bold(),
+                    Severity::Warning => "warning".yellow().bold(),
+                    Severity::Info => "info".cyan().bold(),
+                };
+                out.push_str(&format!(
+                    "{}:{}:{} {} [{}] {}\n",
+                    file.display(),
+                    issue.location.line,
+                    issue.location.column,
+                    severity,
+                    issue.rule.dimmed(),
+                    issue.message
+                ));
+            }
+        }
+
+        let status = if total == 0 {
+            format!("{} {} files", "✓ Checked".green().bold(), self.snapshot.files.len())
+        } else {
+            format!(
+                "{} {} issues in {} of {} files",
+                "Found".red().bold(),
+                total,
+                self.issues.len(),
+                self.snapshot.files.len()
+            )
+        };
+        out.push_str(&format!(
+            "\n{} ({} changed, {:.0}ms)\n",
+            status,
+            self.last_changed,
+            self.last_duration.as_secs_f64() * 1000.0
+        ));
+        out
+    }
+
+    /// Lints once, then re-lints forever as the filesystem reports changes,
+    /// redrawing the summary whenever something changed. Changes made within
+    /// `interval` of each other are handled together. Where the filesystem
+    /// can't report changes, this polls every `interval` instead.
+    pub fn run(&mut self, interval: Duration, out: &mut impl Write) -> io::Result<()> {
+        self.poll();
+
+        let (sender, receiver) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+            watcher.watch(&self.root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        let (_watcher, root) = match (watcher, self.root.canonicalize()) {
+            (Ok(watcher), Ok(root)) => (watcher, root),
+            _ => return self.run_polling(interval, out),
+        };
+        self.redraw(out)?;
+
+        while let Ok(event) = receiver.recv() {
+            let mut events = vec![event];
+            let deadline = Instant::now() + interval;
+            while let Ok(event) = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                events.push(event);
+            }
+
+            if !self.handle_events(events, &root).is_empty() {
+                self.redraw(out)?;
+            }
+        }
+
+        // The watcher stopped, so keep going the slow way
+        self.run_polling(interval, out)
+    }
+
+    fn run_polling(&mut self, interval: Duration, out: &mut impl Write) -> io::Result<()> {
+        self.polling = Some(interval);
+        self.redraw(out)?;
+
+        loop {
+            std::thread::sleep(interval);
+            if !self.poll().is_empty() {
+                self.redraw(out)?;
+            }
+        }
+    }
+
+    fn redraw(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "{}{}", CLEAR_SCREEN, self.summary())?;
+        let status = match self.polling {
+            Some(interval) => format!(
+                "Checking {} for changes every {}ms...",
+                self.root.display(),
+                interval.as_millis()
+            ),
+            None => format!("Watching {} for changes...", self.root.display()),
+        };
+        writeln!(out, "{}", status.dimmed())?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.rs");
+        let edited = temp_dir.path().join("edited.rs");
+        let deleted = temp_dir.path().join("deleted.rs");
+        fs::write(&kept, "fn kept() {}").unwrap();
+        fs::write(&edited, "fn edited() {}").unwrap();
+        fs::write(&deleted, "fn deleted() {}").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "not rust").unwrap();
+
+        let before = FileSnapshot::scan(&RustFileWalker::new(), temp_dir.path());
+        assert_eq!(before.paths(), [deleted.clone(), edited.clone(), kept.clone()]);
+        assert_eq!(before.changes_since(&before), Changes::default());
+
+        let added = temp_dir.path().join("added.rs");
+        fs::write(&added, "fn added() {}").unwrap();
+        fs::write(&edited, "fn edited() { let renamed = 1; }").unwrap();
+        fs::remove_file(&deleted).unwrap();
+
+        let changes = FileSnapshot::scan(&RustFileWalker::new(), temp_dir.path()).changes_since(&before);
+        assert_eq!(changes.modified, [added.clone(), edited.clone()]);
+        assert_eq!(changes.removed, std::slice::from_ref(&deleted));
+
+        // Looking again at only the reported paths finds the same changes
+        let reported = [added.clone(), edited.clone(), deleted.clone(), temp_dir.path().join("notes.txt")];
+        let rescanned = before.rescan(&RustFileWalker::new(), &reported);
+        assert_eq!(rescanned, FileSnapshot::scan(&RustFileWalker::new(), temp_dir.path()));
+
+        // A removed directory takes its files with it
+        let nested = temp_dir.path().join("nested");
+        fs::create_dir(&nested).unwrap();
+        fs::write(nested.join("inner.rs"), "fn inner() {}").unwrap();
+        let with_nested = FileSnapshot::scan(&RustFileWalker::new(), temp_dir.path());
+        fs::remove_dir_all(&nested).unwrap();
+        let changes = with_nested.rescan(&RustFileWalker::new(), std::slice::from_ref(&nested)).changes_since(&with_nested);
+        assert_eq!(changes.removed, [nested.join("inner.rs")]);
+    }
+
+    #[test]
+    fn test_watcher_relints_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        fs::write(&file, "fn run() { let x: Option<i32> = None; x.unwrap(); }").unwrap();
+
+        let mut config = Config::default();
+        config.cache.cache_dir = Some(temp_dir.path().join(".cache"));
+        let mut watcher = Watcher::new(temp_dir.path().to_path_buf(), config);
+
+        assert_eq!(watcher.poll().modified, std::slice::from_ref(&file));
+        assert!(watcher.issues()[&file].iter().any(|issue| issue.rule == "unwrap_usage"));
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&file, "fn run() {}\n// a longer file now").unwrap();
+        assert_eq!(watcher.poll().modified, std::slice::from_ref(&file));
+        assert!(!watcher.issues().contains_key(&file));
+    }
+
+    #[test]
+    fn test_watcher_uses_the_config_above_it() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("lib.rs"), "fn run() { let x: Option<i32> = None; x.unwrap(); }").unwrap();
+        fs::write(src.join("generated.rs"), "fn generated() {}").unwrap();
+
+        let mut config = Config::default();
+        config.rules.check_unwrap_usage = false;
+        config.ignore = vec!["src/generated.rs".to_string()];
+        config.cache.cache_dir = Some(temp_dir.path().join(".cache"));
+        fs::write(temp_dir.path().join(".fl.toml"), toml::to_string(&config).unwrap()).unwrap();
+
+        let mut watcher = Watcher::for_path(src.clone());
+        assert_eq!(watcher.poll().modified, [src.join("lib.rs")]);
+        assert!(watcher.issues().is_empty());
+    }
+
+    #[test]
+    fn test_watcher_handles_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let file = root.join("lib.rs");
+        fs::write(&file, "fn run() {}").unwrap();
+
+        let mut config = Config::default();
+        config.cache.cache_dir = Some(root.join(".cache"));
+        let mut watcher = Watcher::new(root.clone(), config);
+        watcher.poll();
+
+        let modified = |path: &Path| {
+            let kind = notify::EventKind::Modify(notify::event::ModifyKind::Data(notify::event::DataChange::Content));
+            Ok(notify::Event::new(kind).add_path(path.to_path_buf()))
+        };
+        let opened = notify::Event::new(notify::EventKind::Access(notify::event::AccessKind::Any)).add_path(file.clone());
+
+        // Touching a file without changing it is no change
+        assert!(watcher.handle_events(vec![modified(&file), Ok(opened)], &root).is_empty());
+
+        fs::write(&file, "fn run() { let x: Option<i32> = None; x.unwrap(); }").unwrap();
+        let changes = watcher.handle_events(vec![modified(&file), modified(&root.join("notes.txt"))], &root);
+        assert_eq!(changes.modified, std::slice::from_ref(&file));
+        assert!(watcher.issues()[&file].iter().any(|issue| issue.rule == "unwrap_usage"));
+
+        // A file created without an event is only found by a full rescan
+        let missed = root.join("missed.rs");
+        fs::write(&missed, "fn missed() {}").unwrap();
+        assert!(watcher.handle_events(vec![modified(&file)], &root).is_empty());
+        let rescan = notify::Event::new(notify::EventKind::Other).set_flag(notify::event::Flag::Rescan);
+        assert_eq!(watcher.handle_events(vec![Ok(rescan)], &root).modified, [missed]);
+    }
+}
@@ -0,0 +1,123 @@
+// Expected complexity scores, checked by `rules::complexity::tests`. Each
+// function is preceded by its expected cyclomatic complexity, cognitive
+// complexity and maximum nesting depth.
+
+// cyclomatic: 1, cognitive: 0, nesting: 0
+fn straight_line(a: i32, b: i32) -> i32 {
+    let sum = a + b;
+    sum * 2
+}
+
+// cyclomatic: 4, cognitive: 7, nesting: 3
+fn sum_of_primes(max: u32) -> u32 {
+    let mut total = 0;
+    'outer: for i in 1..=max {
+        for j in 2..i {
+            if i % j == 0 {
+                continue 'outer;
+            }
+        }
+        total += i;
+    }
+    total
+}
+
+// cyclomatic: 4, cognitive: 1, nesting: 1
+fn get_words(number: u32) -> &'static str {
+    match number {
+        1 => "one",
+        2 => "a couple",
+        3 => "a few",
+        _ => "lots",
+    }
+}
+
+// cyclomatic: 3, cognitive: 3, nesting: 1
+fn classify(n: i32) -> &'static str {
+    if n < 0 {
+        "negative"
+    } else if n == 0 {
+        "zero"
+    } else {
+        "positive"
+    }
+}
+
+// cyclomatic: 8, cognitive: 5, nesting: 1
+fn logical(a: bool, b: bool, c: bool, d: bool) -> bool {
+    if a && b && c || d {
+        return true;
+    }
+    a || (b || c) && !d
+}
+
+// cyclomatic: 3, cognitive: 3, nesting: 1
+fn parse_all(input: &[&str]) -> Result<Vec<u32>, std::num::ParseIntError> {
+    let values = input
+        .iter()
+        .map(|s| {
+            if s.is_empty() {
+                Ok(0)
+            } else {
+                s.parse::<u32>()
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(values)
+}
+
+// cyclomatic: 2, cognitive: 3, nesting: 1
+fn factorial(n: u64) -> u64 {
+    if n <= 1 { 1 } else { n * factorial(n - 1) }
+}
+
+struct Counter;
+
+impl Counter {
+    // cyclomatic: 2, cognitive: 2, nesting: 1
+    fn countdown(&self, n: u32) -> u32 {
+        match n {
+            0 => 0,
+            _ => self.countdown(n - 1) + 1,
+        }
+    }
+}
+
+// cyclomatic: 6, cognitive: 9, nesting: 3
+fn first_even(values: &mut Vec<i32>) -> Option<i32> {
+    let Some(last) = values.last().copied() else {
+        return None;
+    };
+    let mut found = None;
+    'search: loop {
+        while let Some(value) = values.pop() {
+            if value % 2 == 0 && value != last {
+                found = Some(value);
+                break 'search;
+            }
+        }
+        break;
+    }
+    found
+}
+
+// cyclomatic: 5, cognitive: 3, nesting: 2
+fn describe(pair: (i32, i32)) -> &'static str {
+    match pair {
+        (x, _) if x < 0 => "negative",
+        (0, y) => match y {
+            0 => "origin",
+            _ => "axis",
+        },
+        _ => "other",
+    }
+}
+
+// cyclomatic: 1, cognitive: 0, nesting: 0
+fn outer_with_helper(v: i32) -> i32 {
+    // cyclomatic: 2, cognitive: 2, nesting: 1
+    fn helper(v: i32) -> i32 {
+        if v > 0 { v } else { -v }
+    }
+    helper(v)
+}
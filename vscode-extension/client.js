@@ -1,59 +1,174 @@
+const fs = require('fs');
 const path = require('path');
 const vscode = require('vscode');
 const {
+    DidChangeConfigurationNotification,
+    ExecuteCommandRequest,
     LanguageClient,
-    LanguageClientOptions,
-    ServerOptions,
     TransportKind
 } = require('vscode-languageclient/node');
 
+// Server-side command that lints every file in the workspace folders
+const LINT_WORKSPACE_COMMAND = 'cargo-fl-lsp.lintWorkspace';
+
 let client;
 
 function activate(context) {
-    // The server is implemented in node
-    const serverModule = context.asAbsolutePath(path.join('server.js'));
-    
-    // The debug options for the server
-    const debugOptions = { execArgv: ['--nolazy', '--inspect=6009'] };
-
-    // If the extension is launched in debug mode then the debug server options are used
-    // Otherwise the run options are used
-    const serverOptions = {
-        run: { module: serverModule, transport: TransportKind.ipc },
-        debug: {
-            module: serverModule,
-            transport: TransportKind.ipc,
-            options: debugOptions
-        }
+    client = createClient(context);
+
+    context.subscriptions.push(
+        vscode.commands.registerCommand('cargo-fl.restart', async () => {
+            await client.stop();
+            client = createClient(context);
+            await client.start();
+        }),
+        vscode.commands.registerCommand('cargo-fl.fixAll', fixAll),
+        vscode.commands.registerCommand('cargo-fl.lintWorkspace', lintWorkspace),
+        vscode.commands.registerCommand('cargo-fl.showRuleDocs', showRuleDocs),
+        vscode.workspace.onDidChangeConfiguration(async (event) => {
+            if (event.affectsConfiguration('cargo-fl.server.path')) {
+                vscode.commands.executeCommand('cargo-fl.restart');
+            } else if (event.affectsConfiguration('cargo-fl')) {
+                await client.sendNotification(DidChangeConfigurationNotification.type, {
+                    settings: { 'cargo-fl': serverSettings() }
+                });
+            }
+        })
+    );
+
+    return client.start();
+}
+
+function createClient(context) {
+    const executable = {
+        command: serverPath(context),
+        transport: TransportKind.stdio
     };
 
     // Options to control the language client
     const clientOptions = {
         // Register the server for Rust documents
         documentSelector: [{ scheme: 'file', language: 'rust' }],
-        synchronize: {
-            // Notify the server about file changes to '.rs files contained in the workspace
-            fileEvents: vscode.workspace.createFileSystemWatcher('**/*.rs')
+        initializationOptions: serverSettings(),
+        outputChannelName: 'Cargo Fast Lint',
+        middleware: {
+            workspace: {
+                // Answer the server's settings requests in the shape it expects
+                configuration: async (params, token, next) => {
+                    const result = await next(params, token);
+                    return params.items.map((item, index) =>
+                        item.section === 'cargo-fl' ? serverSettings() : result[index]
+                    );
+                }
+            }
         }
     };
 
-    // Create the language client and start the client.
-    client = new LanguageClient(
-        'cargoFl',
+    return new LanguageClient(
+        'cargo-fl',
         'Cargo Fast Lint Language Server',
-        serverOptions,
+        { run: executable, debug: executable },
         clientOptions
     );
+}
 
-    // Register commands
-    const restartCommand = vscode.commands.registerCommand('cargo-fl.restart', () => {
-        client.restart();
+/**
+ * The server binary: the configured path, else the one bundled for this
+ * platform, else `cargo-fl-lsp` from the PATH.
+ */
+function serverPath(context) {
+    const configured = vscode.workspace.getConfiguration('cargo-fl').get('server.path');
+    if (configured) {
+        return configured;
+    }
+
+    const binary = process.platform === 'win32' ? 'cargo-fl-lsp.exe' : 'cargo-fl-lsp';
+    const bundled = context.asAbsolutePath(
+        path.join('server', `${process.platform}-${process.arch}`, binary)
+    );
+    return fs.existsSync(bundled) ? bundled : binary;
+}
+
+/**
+ * Extension settings translated into overrides of the `.fl.toml` config the
+ * server merges them onto.
+ */
+function serverSettings() {
+    const config = vscode.workspace.getConfiguration('cargo-fl');
+    const settings = {
+        enable: config.get('enable', true),
+        rules: config.get('rules', {})
+    };
+
+    const maxLineLength = config.get('maxLineLength');
+    if (maxLineLength) {
+        settings.style = { max_line_length: maxLineLength };
+    }
+    return settings;
+}
+
+async function fixAll() {
+    if (!vscode.window.activeTextEditor) {
+        return;
+    }
+    await vscode.commands.executeCommand('editor.action.codeAction', {
+        kind: 'source.fixAll.cargo-fl',
+        apply: 'first'
+    });
+}
+
+async function lintWorkspace() {
+    await client.sendRequest(ExecuteCommandRequest.type, {
+        command: LINT_WORKSPACE_COMMAND,
+        arguments: []
     });
+}
+
+/**
+ * Opens the documentation of the rule under the cursor, or of a rule picked
+ * from the current file's findings.
+ */
+async function showRuleDocs() {
+    const editor = vscode.window.activeTextEditor;
+    if (!editor) {
+        return;
+    }
 
-    context.subscriptions.push(restartCommand);
+    const diagnostics = vscode.languages
+        .getDiagnostics(editor.document.uri)
+        .filter((diagnostic) => diagnostic.source === 'cargo-fl' && diagnostic.code);
+    const atCursor = diagnostics.filter((diagnostic) =>
+        diagnostic.range.contains(editor.selection.active)
+    );
+
+    let diagnostic = atCursor[0];
+    if (!diagnostic) {
+        const rules = new Map(diagnostics.map((d) => [ruleName(d), d]));
+        if (rules.size === 0) {
+            vscode.window.showInformationMessage('No cargo-fl findings in this file.');
+            return;
+        }
+        const picked = await vscode.window.showQuickPick([...rules.keys()], {
+            placeHolder: 'Show documentation for rule'
+        });
+        diagnostic = picked && rules.get(picked);
+    }
+    if (!diagnostic) {
+        return;
+    }
+
+    if (typeof diagnostic.code === 'object' && diagnostic.code.target) {
+        await vscode.env.openExternal(diagnostic.code.target);
+    } else {
+        vscode.window.showInformationMessage(
+            `No documentation URL is configured for ${ruleName(diagnostic)}. ` +
+            'Set `[docs] url_template` in .fl.toml to link rules to their docs.'
+        );
+    }
+}
 
-    // Start the client. This will also launch the server
-    client.start();
+function ruleName(diagnostic) {
+    return typeof diagnostic.code === 'object' ? String(diagnostic.code.value) : String(diagnostic.code);
 }
 
 function deactivate() {
@@ -66,4 +181,4 @@ function deactivate() {
 module.exports = {
     activate,
     deactivate
-};
\ No newline at end of file
+};
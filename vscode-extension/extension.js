@@ -1,2 +0,0 @@
-// This file is renamed to client.js - keeping for backup
-module.exports = {};
\ No newline at end of file
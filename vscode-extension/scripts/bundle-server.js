@@ -0,0 +1,19 @@
+// Builds cargo-fl-lsp for this machine and copies it to
+// server/<platform>-<arch>/, where the extension looks for a bundled server.
+// Run on each platform (or in CI per target) before `vsce package --target`.
+const { execFileSync } = require('child_process');
+const fs = require('fs');
+const path = require('path');
+
+const repoRoot = path.resolve(__dirname, '..', '..');
+const binary = process.platform === 'win32' ? 'cargo-fl-lsp.exe' : 'cargo-fl-lsp';
+
+execFileSync('cargo', ['build', '--release', '--bin', 'cargo-fl-lsp'], {
+    cwd: repoRoot,
+    stdio: 'inherit'
+});
+
+const destination = path.join(__dirname, '..', 'server', `${process.platform}-${process.arch}`);
+fs.mkdirSync(destination, { recursive: true });
+fs.copyFileSync(path.join(repoRoot, 'target', 'release', binary), path.join(destination, binary));
+console.log(`Bundled ${binary} into ${path.relative(process.cwd(), destination)}`);